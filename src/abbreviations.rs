@@ -0,0 +1,245 @@
+use crate::json;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// q-codes and cw abbreviations known without a dictionary file
+const STANDARD: &str = "\
+    # q-codes
+    qra name of the station
+    qrg exact frequency
+    qrl frequency is busy
+    qrm interference
+    qrn static noise
+    qro increase power
+    qrp reduce power
+    qrq send faster
+    qrs send slower
+    qrt stop sending
+    qrv ready
+    qrx wait
+    qrz who is calling me
+    qsb signal fading
+    qsl receipt confirmed
+    qso contact
+    qsy change frequency
+    qth location
+    # cw abbreviations
+    73 best regards
+    88 love and kisses
+    agn again
+    ant antenna
+    bk break
+    cfm confirm
+    cl closing down
+    cq calling any station
+    cpy copy
+    de from
+    dx distance
+    es and
+    fb fine business
+    fer for
+    ga good afternoon
+    ge good evening
+    gl good luck
+    gm good morning
+    hr here
+    hw how
+    nr number
+    om old man
+    op operator
+    pse please
+    pwr power
+    r received
+    rig radio
+    rpt report
+    rst signal report
+    sig signal
+    sk end of contact
+    tnx thanks
+    tu thank you
+    ur your
+    wx weather
+    xyl wife
+    yl young lady";
+
+/// how the meanings are shown in the decoded text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbbreviationStyle {
+    /// meaning in brackets after the abbreviation, `qth (location)`
+    Annotate,
+    /// abbreviation replaced by its meaning
+    Expand,
+}
+
+impl FromStr for AbbreviationStyle {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "annotate" => Ok(AbbreviationStyle::Annotate),
+            "expand" => Ok(AbbreviationStyle::Expand),
+            _ => Err(format!("Abbreviation style not found: {}", s)),
+        }
+    }
+}
+
+/// abbreviation found in a text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// position of the word in the text, starting at 0
+    pub index: usize,
+    pub word: String,
+    pub meaning: String,
+}
+
+impl Annotation {
+    pub fn to_json(&self) -> String {
+        json::object([
+            ("index", self.index.to_string()),
+            ("word", json::string(&self.word)),
+            ("meaning", json::string(&self.meaning)),
+        ])
+    }
+}
+
+/// meanings of q-codes and abbreviations, editable
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    meanings: HashMap<String, String>,
+}
+
+// `599` read as readability, strength and tone
+fn signal_report(word: &str) -> Option<String> {
+    let digits: Vec<char> = word.chars().collect();
+    if !(2..=3).contains(&digits.len()) || !digits.iter().all(char::is_ascii_digit) {
+        return None;
+    }
+    let parts: Vec<String> = ["readability", "strength", "tone"]
+        .iter()
+        .zip(&digits)
+        .map(|(name, digit)| format!("{} {}", name, digit))
+        .collect();
+    Some(parts.join(", "))
+}
+
+impl Dictionary {
+    /// one abbreviation a line followed by its meaning, empty lines and lines
+    /// starting with `#` are skipped
+    pub fn parse(text: &str) -> Self {
+        let mut dictionary = Dictionary::default();
+        dictionary.extend(text);
+        dictionary
+    }
+
+    /// the q-codes and the usual cw abbreviations
+    pub fn standard() -> Self {
+        Self::parse(STANDARD)
+    }
+
+    /// add the entries of a dictionary text, replacing the known ones
+    pub fn extend(&mut self, text: &str) -> &mut Self {
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, meaning) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            self.insert(word, meaning.trim());
+        }
+        self
+    }
+
+    pub fn insert(&mut self, word: &str, meaning: &str) -> &mut Self {
+        self.meanings.insert(word.to_lowercase(), meaning.into());
+        self
+    }
+
+    pub fn remove(&mut self, word: &str) -> &mut Self {
+        self.meanings.remove(&word.to_lowercase());
+        self
+    }
+
+    pub fn get(&self, word: &str) -> Option<&str> {
+        self.meanings.get(&word.to_lowercase()).map(String::as_str)
+    }
+
+    /// abbreviations of `text` with their meanings, a `?` after a q-code
+    /// makes it a question and the number after `rst` is read as a report
+    pub fn annotate(&self, text: &str) -> Vec<Annotation> {
+        let mut annotations = Vec::new();
+        let mut previous = "";
+        for (index, word) in text.split_whitespace().enumerate() {
+            let bare = word.trim_matches(|ch: char| ch.is_ascii_punctuation());
+            let meaning = match self.get(bare) {
+                Some(meaning) if word.ends_with('?') && bare.starts_with('q') => {
+                    Some(format!("{}?", meaning))
+                }
+                Some(meaning) => Some(meaning.to_string()),
+                None if previous.eq_ignore_ascii_case("rst") => signal_report(bare),
+                None => None,
+            };
+            if let Some(meaning) = meaning.filter(|meaning| !meaning.is_empty()) {
+                annotations.push(Annotation {
+                    index,
+                    word: bare.into(),
+                    meaning,
+                });
+            }
+            previous = bare;
+        }
+        annotations
+    }
+
+    /// `text` with the meanings shown in the given style
+    pub fn render(&self, text: &str, style: AbbreviationStyle) -> String {
+        let annotations = self.annotate(text);
+        let words: Vec<String> = text
+            .split_whitespace()
+            .enumerate()
+            .map(|(index, word)| {
+                match annotations
+                    .iter()
+                    .find(|annotation| annotation.index == index)
+                {
+                    Some(annotation) => match style {
+                        AbbreviationStyle::Annotate => format!("{} ({})", word, annotation.meaning),
+                        AbbreviationStyle::Expand => annotation.meaning.clone(),
+                    },
+                    None => word.to_string(),
+                }
+            })
+            .collect();
+        words.join(" ")
+    }
+}
+
+#[test]
+fn test_abbreviations() {
+    let mut dictionary = Dictionary::standard();
+    let text = "cq de k1abc ur rst 599 qth boston qrz? tnx 73";
+    let annotations = dictionary.annotate(text);
+    let words: Vec<&str> = annotations.iter().map(|a| a.word.as_str()).collect();
+    assert_eq!(
+        words,
+        ["cq", "de", "ur", "rst", "599", "qth", "qrz", "tnx", "73"]
+    );
+    assert_eq!(annotations[4].meaning, "readability 5, strength 9, tone 9");
+    assert_eq!(annotations[6].meaning, "who is calling me?");
+    assert_eq!(
+        dictionary.render("qth boston", AbbreviationStyle::Annotate),
+        "qth (location) boston"
+    );
+    assert_eq!(
+        dictionary.render("tnx fer call", AbbreviationStyle::Expand),
+        "thanks for call"
+    );
+
+    // the dictionary can be edited
+    dictionary
+        .extend("# club words\nhi laughter\nde\n")
+        .remove("tnx");
+    assert_eq!(dictionary.get("HI"), Some("laughter"));
+    assert!(dictionary.annotate("tnx de w1aw").is_empty());
+    assert_eq!(
+        annotations[0].to_json(),
+        r#"{"index":0,"word":"cq","meaning":"calling any station"}"#
+    );
+}
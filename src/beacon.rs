@@ -0,0 +1,182 @@
+use crate::keying::WORD_GAP_UNITS;
+use crate::markup::Voice;
+use crate::polyphonia::SAMPLE_RATE;
+
+// samples handed out at a time, one second of audio
+const BLOCK_SIZE: usize = SAMPLE_RATE as usize;
+
+/// a message repeated with a long carrier and some silence after every copy,
+/// as fox-hunt and propagation beacons send it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Beacon {
+    /// copies of the message, endless until `duration` when not set
+    pub repeat: Option<usize>,
+    /// seconds of audio at most, the last cycle is cut
+    pub duration: Option<f32>,
+    /// seconds of steady tone after the message
+    pub carrier: f32,
+    /// seconds of silence after the carrier
+    pub gap: f32,
+    /// tone of the carrier
+    pub voice: Voice,
+}
+
+impl Default for Beacon {
+    fn default() -> Self {
+        Beacon {
+            repeat: None,
+            duration: None,
+            carrier: 5.0,
+            gap: 10.0,
+            voice: Voice::default(),
+        }
+    }
+}
+
+impl Beacon {
+    /// blocks of samples of the whole beacon, generated as they are asked for
+    /// so that hours of audio never sit in memory
+    pub fn chunks<'m>(&self, message: &'m [f32]) -> BeaconChunks<'m> {
+        let seconds = |seconds: f32| (seconds.max(0.0) * SAMPLE_RATE as f32) as usize;
+        BeaconChunks {
+            message,
+            segments: [
+                message.len(),
+                seconds(self.voice.gap_unit() * WORD_GAP_UNITS as f32),
+                seconds(self.carrier),
+                seconds(self.gap),
+            ],
+            voice: self.voice,
+            cycles: match (self.repeat, self.duration) {
+                (None, None) => Some(1),
+                (repeat, _) => repeat,
+            },
+            limit: self.duration.map(seconds),
+            cycle: 0,
+            segment: 0,
+            position: 0,
+            written: 0,
+        }
+    }
+}
+
+/// iterator over the blocks of a `Beacon`
+pub struct BeaconChunks<'m> {
+    message: &'m [f32],
+    // samples of the message, the word gap, the carrier and the silence
+    segments: [usize; 4],
+    voice: Voice,
+    cycles: Option<usize>,
+    limit: Option<usize>,
+    cycle: usize,
+    segment: usize,
+    position: usize,
+    written: usize,
+}
+
+impl BeaconChunks<'_> {
+    // `len` samples of the segment from the current position
+    fn samples(&self, len: usize) -> Vec<f32> {
+        match self.segment {
+            0 => self.message[self.position..self.position + len].to_vec(),
+            2 => {
+                // an f32 phase loses precision after a few minutes of carrier
+                let w =
+                    2.0 * std::f64::consts::PI * self.voice.frequency as f64 / SAMPLE_RATE as f64;
+                (self.position..self.position + len)
+                    .map(|t| self.voice.volume * (w * t as f64).sin() as f32)
+                    .collect()
+            }
+            _ => vec![0.0; len],
+        }
+    }
+}
+
+impl Iterator for BeaconChunks<'_> {
+    type Item = Vec<f32>;
+
+    fn next(&mut self) -> Option<Vec<f32>> {
+        // nothing would ever be sent
+        if self.segments.iter().sum::<usize>() == 0 {
+            return None;
+        }
+        loop {
+            let left = self.limit.map_or(usize::MAX, |limit| limit - self.written);
+            if left == 0 || self.cycles.is_some_and(|cycles| self.cycle >= cycles) {
+                return None;
+            }
+            let segment_left = self.segments[self.segment] - self.position;
+            if segment_left == 0 {
+                self.position = 0;
+                self.segment += 1;
+                if self.segment == self.segments.len() {
+                    self.segment = 0;
+                    self.cycle += 1;
+                }
+                continue;
+            }
+            let len = segment_left.min(left).min(BLOCK_SIZE);
+            let chunk = self.samples(len);
+            self.position += len;
+            self.written += len;
+            return Some(chunk);
+        }
+    }
+}
+
+#[test]
+fn test_beacon() {
+    let message = vec![0.03; SAMPLE_RATE as usize / 2];
+    let beacon = Beacon {
+        repeat: Some(3),
+        carrier: 2.5,
+        gap: 1.0,
+        ..Default::default()
+    };
+    let chunks: Vec<Vec<f32>> = beacon.chunks(&message).collect();
+    assert!(chunks.iter().all(|chunk| chunk.len() <= BLOCK_SIZE));
+    let samples: Vec<f32> = chunks.concat();
+    // message, word gap, carrier and silence three times
+    let cycle = SAMPLE_RATE as f32 * (0.5 + 0.7 + 2.5 + 1.0);
+    assert!((samples.len() as f32 - 3.0 * cycle).abs() < 3.0);
+    let carrier_start = (SAMPLE_RATE as f32 * 1.2) as usize + 10;
+    assert!(samples[carrier_start..carrier_start + 100]
+        .iter()
+        .any(|x| x.abs() > 0.3));
+    assert!(samples[samples.len() - 100..].iter().all(|x| *x == 0.0));
+
+    // a set duration cuts the last cycle, and ends a beacon sent without end
+    let beacon = Beacon {
+        duration: Some(10.0),
+        ..beacon
+    };
+    let samples = |beacon: Beacon| {
+        beacon
+            .chunks(&message)
+            .map(|chunk| chunk.len())
+            .sum::<usize>()
+    };
+    assert_eq!(samples(beacon), 10 * SAMPLE_RATE as usize);
+    assert!(
+        samples(Beacon {
+            repeat: Some(1),
+            ..beacon
+        }) < 5 * SAMPLE_RATE as usize
+    );
+    let endless = Beacon {
+        repeat: None,
+        duration: Some(60.0),
+        ..beacon
+    };
+    assert_eq!(samples(endless), 60 * SAMPLE_RATE as usize);
+
+    // the carrier is still a clean sine after an hour
+    let mut chunks = beacon.chunks(&message);
+    chunks.segment = 2;
+    chunks.position = 3600 * SAMPLE_RATE as usize;
+    let carrier = chunks.samples(100);
+    let w = 2.0 * std::f32::consts::PI * beacon.voice.frequency / SAMPLE_RATE as f32;
+    assert!(carrier
+        .windows(3)
+        .all(|x| (x[2] - (2.0 * w.cos() * x[1] - x[0])).abs() < 1e-4));
+}
@@ -0,0 +1,198 @@
+use crate::language::WordList;
+use crate::{morse_alphabet, Letter};
+use std::str::FromStr;
+
+/// a letter or a word replaced with the nearest valid one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    /// dots and dashes received, letters separated by spaces
+    pub received: String,
+    pub text: String,
+    /// dots, dashes and letter gaps added, removed or swapped to get the correction
+    pub distance: usize,
+    /// other readings just as near
+    pub alternatives: Vec<String>,
+}
+
+/// decoded word with the corrections applied to it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FuzzyWord {
+    pub text: String,
+    pub corrections: Vec<Correction>,
+}
+
+impl FuzzyWord {
+    /// the text with corrected words in square brackets
+    pub fn flagged(&self) -> String {
+        if self.corrections.is_empty() {
+            self.text.clone()
+        } else {
+            format!("[{}]", self.text)
+        }
+    }
+}
+
+/// levenshtein distance between two sequences of symbols
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// items of `options` nearest to `received`, with their distance
+fn nearest<'a, I: IntoIterator<Item = (&'a str, String)>>(
+    received: &str,
+    options: I,
+    max_distance: usize,
+) -> Option<(usize, Vec<&'a str>)> {
+    let mut best: Option<(usize, Vec<&'a str>)> = None;
+    for (text, morse) in options {
+        let distance = edit_distance(received, &morse);
+        if distance > max_distance {
+            continue;
+        }
+        match best.as_mut() {
+            Some((best_distance, texts)) if distance == *best_distance => texts.push(text),
+            Some((best_distance, _)) if distance > *best_distance => {}
+            _ => best = Some((distance, vec![text])),
+        }
+    }
+    best
+}
+
+/// decode tolerating dropped or extra dots and dashes: unknown codes become
+/// the nearest letter, and with a word list unknown words the nearest word
+pub struct FuzzyDecoder<'w> {
+    word_list: Option<&'w WordList>,
+    /// corrections further than this are not tried
+    pub max_distance: usize,
+}
+
+impl<'w> FuzzyDecoder<'w> {
+    pub fn new(word_list: Option<&'w WordList>) -> Self {
+        FuzzyDecoder {
+            word_list,
+            max_distance: 2,
+        }
+    }
+
+    fn correct_code(&self, code: &str) -> Option<Correction> {
+        let letters = morse_alphabet::LETTERS
+            .iter()
+            .map(|Letter(text, morse)| (*text, morse.to_string()));
+        let (distance, mut texts) = nearest(code, letters, self.max_distance)?;
+        Some(Correction {
+            received: code.into(),
+            text: texts.remove(0).into(),
+            distance,
+            alternatives: texts.into_iter().map(String::from).collect(),
+        })
+    }
+
+    // morse of a dictionary word, none when it has characters without a code
+    fn word_morse(word: &str) -> Option<String> {
+        let codes: Option<Vec<&str>> = word
+            .chars()
+            .map(|ch| Letter::from_char(ch).ok().map(|Letter(_, morse)| morse))
+            .collect();
+        codes.map(|codes| codes.join(" "))
+    }
+
+    fn correct_word(&self, received: &str, word_list: &WordList) -> Option<Correction> {
+        let words = word_list
+            .words()
+            .filter_map(|word| Self::word_morse(word).map(|morse| (word, morse)));
+        let (distance, mut texts) = nearest(received, words, self.max_distance)?;
+        texts.sort();
+        Some(Correction {
+            received: received.into(),
+            text: texts.remove(0).into(),
+            distance,
+            alternatives: texts.into_iter().map(String::from).collect(),
+        })
+    }
+
+    /// decode the codes of a single word
+    pub fn decode_word(&self, codes: &[&str]) -> FuzzyWord {
+        let mut word = FuzzyWord::default();
+        for code in codes {
+            match Letter::from_str(code) {
+                Ok(Letter(text, _)) => word.text.push_str(text),
+                Err(_) => match self.correct_code(code) {
+                    Some(correction) => {
+                        word.text.push_str(&correction.text);
+                        word.corrections.push(correction);
+                    }
+                    None => word.text.push('*'),
+                },
+            }
+        }
+        // the error prosign is never in the word list, the word it is in is kept
+        // as received for `prosigns::handle_errors`
+        let Letter(error, _) = morse_alphabet::ERROR;
+        if let Some(word_list) = self.word_list.filter(|_| !word.text.contains(error)) {
+            if !word_list.contains(&word.text) {
+                if let Some(correction) = self.correct_word(&codes.join(" "), word_list) {
+                    word.text = correction.text.clone();
+                    word.corrections = vec![correction];
+                }
+            }
+        }
+        word
+    }
+
+    /// decode a line of codes separated by spaces, with `/` between words
+    pub fn decode_line(&self, line: &str) -> Vec<FuzzyWord> {
+        line.split('/')
+            .map(|word| word.split_whitespace().collect::<Vec<&str>>())
+            .filter(|codes| !codes.is_empty())
+            .map(|codes| self.decode_word(&codes))
+            .collect()
+    }
+}
+
+#[test]
+fn test_fuzzy() {
+    assert_eq!(edit_distance("....", "..-."), 1);
+    assert_eq!(edit_distance(".-.", ""), 3);
+
+    // an extra dot in the first letter, six dots have no letter
+    let decoder = FuzzyDecoder::new(None);
+    let words = decoder.decode_line("...... . .-.. .-.. --- / .-- --- .-. .-.. -..");
+    assert_eq!(words[0].text, "5ello");
+    assert_eq!(words[0].corrections[0].received, "......");
+    assert_eq!(words[0].flagged(), "[5ello]");
+    assert_eq!(words[1].flagged(), "world");
+
+    // the word list fixes letters that are valid but wrong
+    let word_list = WordList::new(["hello", "world"]);
+    let decoder = FuzzyDecoder::new(Some(&word_list));
+    let words = decoder.decode_line("...... . .-.. .-.. --- / .-- --- .-. .-.. -.-");
+    assert_eq!(words[0].text, "hello");
+    assert_eq!(words[1].text, "world");
+    assert_eq!(words[1].corrections[0].distance, 1);
+    assert!(decoder
+        .decode_line("--.- .-. --..")
+        .iter()
+        .all(|w| w.text == "qrz"));
+    // the error prosign is not corrected to a listed word
+    let word_list = WordList::new(["hi", "there", "test"]);
+    let decoder = FuzzyDecoder::new(Some(&word_list));
+    let words = decoder.decode_line("- . ... - / ........ / - .... . .-. .");
+    let text: Vec<String> = words.iter().map(FuzzyWord::flagged).collect();
+    assert_eq!(text, ["test", "<hh>", "there"]);
+    // a period in a listed word is punctuation, not the code of e
+    assert_eq!(
+        FuzzyDecoder::word_morse("e.g.").unwrap(),
+        ". .-.-.- --. .-.-.-"
+    );
+}
@@ -0,0 +1,182 @@
+use std::str::FromStr;
+
+// letter sent for every digit, the digits without one are never cut
+const CUTS: [Option<char>; 10] = [
+    Some('t'),
+    Some('a'),
+    Some('u'),
+    Some('v'),
+    None,
+    Some('e'),
+    None,
+    Some('b'),
+    Some('d'),
+    Some('n'),
+];
+
+/// digits sent as shorter letters in contest exchanges, `5nn` for `599`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CutNumbers {
+    cuts: [Option<char>; 10],
+}
+
+impl Default for CutNumbers {
+    /// the usual `t` for 0 and `n` for 9
+    fn default() -> Self {
+        Self::new("09")
+    }
+}
+
+impl FromStr for CutNumbers {
+    type Err = String;
+    /// `all` or the digits to cut, e.g. `09`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::all());
+        }
+        match s.chars().find(|ch| !ch.is_ascii_digit()) {
+            Some(ch) => Err(format!("Not a digit to cut: {}", ch)),
+            None => Ok(Self::new(s)),
+        }
+    }
+}
+
+impl CutNumbers {
+    /// cut only the given digits, with their usual letters
+    pub fn new(digits: &str) -> Self {
+        let mut cuts = [None; 10];
+        for digit in digits.chars().filter_map(|ch| ch.to_digit(10)) {
+            cuts[digit as usize] = CUTS[digit as usize];
+        }
+        CutNumbers { cuts }
+    }
+
+    /// every digit that has a usual cut
+    pub fn all() -> Self {
+        CutNumbers { cuts: CUTS }
+    }
+
+    /// send `digit` as `letter`, e.g. `0` as `o`, a letter cuts a single digit
+    pub fn cut(&mut self, digit: char, letter: char) -> Result<&mut Self, String> {
+        let index = digit
+            .to_digit(10)
+            .ok_or_else(|| format!("Not a digit to cut: {}", digit))? as usize;
+        if !letter.is_ascii_alphabetic() {
+            return Err(format!("Not a letter to cut to: {}", letter));
+        }
+        let letter = letter.to_ascii_lowercase();
+        match self.digit(letter) {
+            Some(other) if other != digit => Err(format!(
+                "The letter {} already cuts the digit {}",
+                letter, other
+            )),
+            _ => {
+                self.cuts[index] = Some(letter);
+                Ok(self)
+            }
+        }
+    }
+
+    fn digit(&self, letter: char) -> Option<char> {
+        let letter = letter.to_ascii_lowercase();
+        let digit = self.cuts.iter().position(|cut| *cut == Some(letter))?;
+        char::from_digit(digit as u32, 10)
+    }
+
+    // only digits and cut letters, with at least one digit unless `sure`
+    fn is_cut_number(&self, word: &str, sure: bool) -> bool {
+        !word.is_empty()
+            && word
+                .chars()
+                .all(|ch| ch.is_ascii_digit() || self.digit(ch).is_some())
+            && (sure || word.chars().any(|ch| ch.is_ascii_digit()))
+    }
+
+    fn is_callsign(&self, word: &str) -> bool {
+        word.chars().any(|ch| ch.is_ascii_digit())
+            && word.chars().any(|ch| ch.is_ascii_alphabetic())
+            && word
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '/')
+            && !self.is_cut_number(word, false)
+    }
+
+    /// numbers of `text` with their digits cut, callsigns and other words
+    /// with letters are left alone
+    pub fn shorten(&self, text: &str) -> String {
+        let words: Vec<String> = text
+            .split(' ')
+            .map(|word| {
+                if !word.is_empty() && word.chars().all(|ch| ch.is_ascii_digit()) {
+                    word.chars()
+                        .map(|ch| self.cuts[ch.to_digit(10).unwrap() as usize].unwrap_or(ch))
+                        .collect()
+                } else {
+                    word.to_string()
+                }
+            })
+            .collect();
+        words.join(" ")
+    }
+
+    /// cut numbers of `text` turned back into digits where a number is expected:
+    /// after `rst` or `nr`, after a callsign, and once after another number
+    /// as a serial follows the report
+    pub fn expand(&self, text: &str) -> String {
+        let mut words: Vec<String> = Vec::new();
+        let mut previous_number = false;
+        for word in text.split(' ') {
+            let chained = previous_number;
+            let previous = words.last().map(|word| word.to_ascii_lowercase());
+            let sure = previous_number || matches!(previous.as_deref(), Some("rst" | "nr"));
+            let after_callsign = previous.is_some_and(|word| self.is_callsign(&word));
+            if self.is_cut_number(word, sure) && (sure || after_callsign) {
+                words.push(
+                    word.chars()
+                        .map(|ch| self.digit(ch).unwrap_or(ch))
+                        .collect(),
+                );
+                previous_number = !chained;
+            } else {
+                words.push(word.to_string());
+                previous_number = false;
+            }
+        }
+        words.join(" ")
+    }
+}
+
+#[test]
+fn test_cut_numbers() {
+    let cuts = CutNumbers::default();
+    assert_eq!(cuts.shorten("k1abc 599 001 tu"), "k1abc 5nn tt1 tu");
+    assert_eq!(
+        CutNumbers::all().shorten("ur rst 579 nr 120"),
+        "ur rst ebn nr aut"
+    );
+    assert_eq!(
+        "1a".parse::<CutNumbers>(),
+        Err("Not a digit to cut: a".into())
+    );
+
+    let cuts = CutNumbers::all();
+    assert_eq!(cuts.expand("k1abc 5nn tt1 tu"), "k1abc 599 001 tu");
+    assert_eq!(cuts.expand("k1abc 5nn tta tu"), "k1abc 599 001 tu");
+    assert_eq!(cuts.expand("ur rst ebn nr aut"), "ur rst 579 nr 120");
+    // words that only look like cut numbers are kept
+    assert_eq!(cuts.expand("tnx ant at 5 feet"), "tnx ant at 5 feet");
+    assert_eq!(cuts.expand("de w1aw tu"), "de w1aw tu");
+
+    let mut cuts = CutNumbers::new("");
+    cuts.cut('0', 'O').unwrap();
+    assert_eq!(cuts.shorten("100"), "1oo");
+    assert_eq!(cuts.expand("nr 1oo"), "nr 100");
+    assert_eq!(cuts.cut('x', 'a'), Err("Not a digit to cut: x".into()));
+    assert!(cuts.cut('1', '1').is_err());
+    // a letter cutting two digits could not be read back
+    assert_eq!(
+        cuts.cut('1', 'o'),
+        Err("The letter o already cuts the digit 0".into())
+    );
+    assert!(cuts.cut('0', 't').is_ok());
+}
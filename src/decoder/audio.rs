@@ -0,0 +1,373 @@
+use super::goertzel::goertzel;
+use super::report::DecodeReport;
+use super::spectrum::{average_spectrum, magnitude_spectrum, peak};
+use super::timing::{decode_runs, runs_from_states, AdaptiveClassifier, Run, Transcript};
+use crate::dsp::{Agc, Biquad, Chain, Stage};
+use crate::Letter;
+
+// envelopes quieter than this are considered silence
+pub(crate) const MIN_LEVEL: f32 = 1e-3;
+// seconds of audio, at least, in every spectrum used to find the tone
+const SPECTRUM_DURATION: f32 = 0.05;
+// a drifting tone is followed up to this many Hz away from the dominant one
+const DRIFT_RANGE: f32 = 50.0;
+
+/// frequency of the tone followed by the decoder
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToneTrack {
+    /// strongest tone in the whole recording
+    pub frequency: f32,
+    /// seconds between the points of `drift`
+    pub step: f32,
+    /// frequency followed over time, starting from the middle of the first window
+    pub drift: Vec<f32>,
+    // seconds from the start to the first point of `drift`
+    offset: f32,
+}
+
+impl ToneTrack {
+    pub fn fixed(frequency: f32) -> Self {
+        ToneTrack {
+            frequency,
+            step: f32::INFINITY,
+            drift: vec![frequency],
+            offset: 0.0,
+        }
+    }
+
+    /// frequency of the tone at `time` seconds
+    pub fn at(&self, time: f32) -> f32 {
+        let index = ((time - self.offset) / self.step).round().max(0.0) as usize;
+        self.drift
+            .get(index.min(self.drift.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or(self.frequency)
+    }
+}
+
+/// decode morse sent as an on/off tone
+#[derive(Debug, Clone, Copy)]
+pub struct AudioDecoder {
+    /// pitch of the tone in Hz, searched between `min_frequency`
+    /// and `max_frequency` when not set
+    pub frequency: Option<f32>,
+    pub min_frequency: f32,
+    pub max_frequency: f32,
+    /// follow a tone that slowly changes pitch
+    pub track_drift: bool,
+    /// length in seconds of the blocks analyzed to follow the tone
+    pub block_duration: f32,
+    /// width in Hz of a band-pass filter centered on the tone, applied before detection
+    pub bandwidth: Option<f32>,
+    /// even out fading with an automatic gain control before detection
+    pub agc: bool,
+}
+
+impl Default for AudioDecoder {
+    fn default() -> Self {
+        AudioDecoder {
+            frequency: None,
+            min_frequency: 300.0,
+            max_frequency: 1200.0,
+            track_drift: true,
+            block_duration: 0.005,
+            bandwidth: None,
+            agc: false,
+        }
+    }
+}
+
+impl AudioDecoder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub(crate) fn block_size(&self, sample_rate: u32) -> usize {
+        ((self.block_duration * sample_rate as f32).round() as usize).max(1)
+    }
+
+    /// find the dominant tone with a spectral peak search, then follow
+    /// it window by window when `track_drift` is set
+    pub fn tone_track(&self, samples: &[f32], sample_rate: u32) -> Option<ToneTrack> {
+        if let Some(frequency) = self.frequency {
+            return Some(ToneTrack::fixed(frequency));
+        }
+        let size = ((sample_rate as f32 * SPECTRUM_DURATION) as usize).next_power_of_two();
+        let bin_width = sample_rate as f32 / size as f32;
+        let mut padded = samples.to_vec();
+        if padded.len() < size {
+            padded.resize(size, 0.0);
+        }
+        let (frequency, _) = peak(
+            &average_spectrum(&padded, size),
+            bin_width,
+            self.min_frequency,
+            self.max_frequency,
+        )?;
+        if !self.track_drift {
+            return Some(ToneTrack::fixed(frequency));
+        }
+
+        let hop = size / 2;
+        let peaks: Vec<(f32, f32)> = (0..padded.len() - size + 1)
+            .step_by(hop)
+            .filter_map(|start| {
+                peak(
+                    &magnitude_spectrum(&padded[start..start + size]),
+                    bin_width,
+                    frequency - DRIFT_RANGE,
+                    frequency + DRIFT_RANGE,
+                )
+            })
+            .collect();
+        let loudest = peaks.iter().map(|(_, level)| *level).fold(0_f32, f32::max);
+        // windows without the tone keep the last frequency heard
+        let mut current = frequency;
+        let drift = peaks
+            .iter()
+            .map(|(peak_frequency, level)| {
+                if *level > loudest * 0.3 {
+                    current += (peak_frequency - current) * 0.5;
+                }
+                current
+            })
+            .collect();
+        Some(ToneTrack {
+            frequency,
+            step: hop as f32 / sample_rate as f32,
+            drift,
+            offset: hop as f32 / sample_rate as f32,
+        })
+    }
+
+    /// preprocessing stages selected by `bandwidth` and `agc`
+    pub fn conditioning(&self, sample_rate: u32, tone: &ToneTrack) -> Chain {
+        let mut chain = Chain::new();
+        if let Some(bandwidth) = self.bandwidth {
+            let q = tone.frequency / bandwidth.max(1.0);
+            chain.push(Biquad::band_pass(sample_rate as f32, tone.frequency, q));
+        }
+        if self.agc {
+            chain.push(Agc::new(sample_rate as f32, 0.5));
+        }
+        chain
+    }
+
+    /// amplitude of the tone for every block of samples
+    pub fn envelope(&self, samples: &[f32], sample_rate: u32, tone: &ToneTrack) -> Vec<f32> {
+        let block_size = self.block_size(sample_rate);
+        let mut chain = self.conditioning(sample_rate, tone);
+        let mut conditioned: Vec<f32>;
+        let samples = if chain.is_empty() {
+            samples
+        } else {
+            conditioned = samples.to_vec();
+            chain.process_all(&mut conditioned);
+            &conditioned
+        };
+        samples
+            .chunks(block_size)
+            .enumerate()
+            .map(|(i, block)| {
+                let time = (i * block_size) as f32 / sample_rate as f32;
+                goertzel(block, tone.at(time), sample_rate as f32)
+            })
+            .collect()
+    }
+
+    /// seconds of audio in every key state
+    pub fn step(&self, sample_rate: u32) -> f32 {
+        self.block_size(sample_rate) as f32 / sample_rate as f32
+    }
+
+    /// midpoint between the noise floor and the loudest block,
+    /// not available when the envelope is all silence
+    pub fn threshold(envelope: &[f32]) -> Option<f32> {
+        let mut sorted = envelope.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let floor = sorted.get(sorted.len() / 10)?;
+        let peak = sorted.last()?;
+        (*peak >= MIN_LEVEL).then_some(floor + (peak - floor) / 2.0)
+    }
+
+    /// key state of every block, down when the envelope is above the threshold
+    pub fn key_states(&self, samples: &[f32], sample_rate: u32, tone: &ToneTrack) -> Vec<bool> {
+        let envelope = self.envelope(samples, sample_rate, tone);
+        match Self::threshold(&envelope) {
+            Some(threshold) => envelope.iter().map(|level| *level > threshold).collect(),
+            None => vec![false; envelope.len()],
+        }
+    }
+
+    pub fn runs(&self, samples: &[f32], sample_rate: u32, tone: &ToneTrack) -> Vec<Run> {
+        runs_from_states(
+            self.key_states(samples, sample_rate, tone),
+            self.step(sample_rate),
+        )
+    }
+
+    /// decoded letters with the speed of the sender over time and the tone used
+    pub fn transcribe(&self, samples: &[f32], sample_rate: u32) -> Result<Transcript, String> {
+        let Some(tone) = self.tone_track(samples, sample_rate) else {
+            return Ok(Transcript::default());
+        };
+        let mut transcript = decode_runs(&self.runs(samples, sample_rate, &tone))?;
+        transcript.frequency = Some(tone.frequency);
+        Ok(transcript)
+    }
+
+    pub fn decode(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+    ) -> Result<Vec<Letter<'static>>, String> {
+        Ok(self.transcribe(samples, sample_rate)?.letters)
+    }
+
+    /// decode with timestamp, SNR, timing confidence and alternatives for every character,
+    /// codes without a letter are kept instead of failing
+    pub fn report(&self, samples: &[f32], sample_rate: u32) -> DecodeReport {
+        let Some(tone) = self.tone_track(samples, sample_rate) else {
+            return DecodeReport::default();
+        };
+        let envelope = self.envelope(samples, sample_rate, &tone);
+        let Some(threshold) = Self::threshold(&envelope) else {
+            return DecodeReport::default();
+        };
+        let states: Vec<bool> = envelope.iter().map(|level| *level > threshold).collect();
+        let silence: Vec<f32> = envelope
+            .iter()
+            .copied()
+            .filter(|l| *l <= threshold)
+            .collect();
+        let noise = silence.iter().sum::<f32>() / silence.len().max(1) as f32;
+        let step = self.step(sample_rate);
+        let first = states.iter().position(|key_down| *key_down).unwrap_or(0);
+
+        let runs = runs_from_states(states, step);
+        // mean tone level of every run
+        let mut block = first;
+        let levels: Vec<f32> = runs
+            .iter()
+            .map(|run| {
+                let blocks = ((run.duration / step).round() as usize).max(1);
+                let end = (block + blocks).min(envelope.len());
+                let level = envelope[block..end].iter().sum::<f32>() / blocks as f32;
+                block = end;
+                level
+            })
+            .collect();
+        let (symbols, speed) = AdaptiveClassifier::new().symbols(&runs);
+        DecodeReport {
+            frequency: Some(tone.frequency),
+            speed,
+            characters: DecodeReport::characters(&symbols, &levels, noise, first as f32 * step),
+        }
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    use crate::polyphonia::{MAX_AMPLITUDE, SAMPLE_RATE};
+    use std::str::FromStr;
+
+    for message in ["sos", "hello world", "e t 73", "paris"] {
+        let letters = message
+            .chars()
+            .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+        let samples: Vec<f32> = Letter::concat_audio(letters)
+            .iter()
+            .map(|x| *x as f32 / MAX_AMPLITUDE)
+            .collect();
+        let decoded = AudioDecoder::new().decode(&samples, SAMPLE_RATE).unwrap();
+        assert_eq!(
+            String::from_utf8(Letter::concat_text(decoded)).unwrap(),
+            message
+        );
+    }
+}
+
+#[test]
+fn test_tone_detection() {
+    use crate::keying::{keying, render};
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+    use std::str::FromStr;
+
+    let letters = "cq test"
+        .chars()
+        .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+    // a tone drifting from 900 Hz to 920 Hz
+    let mut samples: Vec<f32> = Vec::new();
+    for key in keying(letters) {
+        let pitch = 900.0 + 20.0 * samples.len() as f32 / (SAMPLE_RATE * 4) as f32;
+        samples.extend(render(&[key], &Note(pitch), 0.06, &Amplitude::Medium));
+    }
+    let decoder = AudioDecoder::new();
+    let transcript = decoder.transcribe(&samples, SAMPLE_RATE).unwrap();
+    assert_eq!(
+        String::from_utf8(Letter::concat_text(transcript.letters)).unwrap(),
+        "cq test"
+    );
+    let frequency = transcript.frequency.unwrap();
+    assert!((900.0..925.0).contains(&frequency), "{}", frequency);
+    let tone = decoder.tone_track(&samples, SAMPLE_RATE).unwrap();
+    assert!(tone.at(0.0) < tone.at(3.5));
+}
+
+#[test]
+fn test_report() {
+    use crate::polyphonia::{MAX_AMPLITUDE, SAMPLE_RATE};
+    use std::str::FromStr;
+
+    let letters = "qrz"
+        .chars()
+        .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+    let mut samples = vec![0.0; SAMPLE_RATE as usize / 2];
+    samples.extend(
+        Letter::concat_audio(letters)
+            .iter()
+            .map(|x| *x as f32 / MAX_AMPLITUDE),
+    );
+    let report = AudioDecoder::new().report(&samples, SAMPLE_RATE);
+    assert_eq!(report.text(), "qrz");
+    assert!((report.characters[0].time - 0.5).abs() < 0.01);
+    assert!(report.characters.iter().all(|ch| ch.confidence > 0.8));
+    assert!(report.characters[0].snr.unwrap() > 20.0);
+    assert!(report.to_json().starts_with("{\"frequency\":440"));
+}
+
+#[test]
+fn test_conditioning() {
+    use crate::keying::{keying, render};
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+    use crate::wav::sample::Dither;
+    use std::str::FromStr;
+
+    let letters = "test de hb9xyz"
+        .chars()
+        .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+    let mut samples = render(
+        &keying(letters),
+        &Note(700.0),
+        0.06,
+        &Amplitude::Custom(1.0),
+    );
+    // deep fading under a noise floor
+    let len = samples.len() as f32;
+    let mut noise = Dither::new(7);
+    for (i, sample) in samples.iter_mut().enumerate() {
+        let fading = 0.05 + 0.95 * (i as f32 / len * 6.0).sin().abs();
+        *sample = *sample * fading + 0.2 * noise.next_offset() as f32;
+    }
+    let decoder = AudioDecoder {
+        frequency: Some(700.0),
+        bandwidth: Some(100.0),
+        agc: true,
+        ..Default::default()
+    };
+    let decoded = decoder.decode(&samples, SAMPLE_RATE).unwrap();
+    assert_eq!(
+        String::from_utf8(Letter::concat_text(decoded)).unwrap(),
+        "test de hb9xyz"
+    );
+}
@@ -0,0 +1,160 @@
+use super::timing::{decode_runs, Run, Transcript};
+use crate::json::{self, Value};
+
+/// the key went down or up at `time` seconds from the first event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEvent {
+    pub time: f32,
+    pub key_down: bool,
+}
+
+// names used by loggers for the two key states
+fn parse_state(state: &str) -> Option<bool> {
+    match state.trim().to_ascii_lowercase().as_str() {
+        "down" | "on" | "1" | "true" | "press" | "pressed" => Some(true),
+        "up" | "off" | "0" | "false" | "release" | "released" => Some(false),
+        _ => None,
+    }
+}
+
+// times read in full precision, as epoch timestamps need, and made relative
+// to the first event so that they fit an f32
+fn from_start(events: Vec<(f64, bool)>) -> Vec<KeyEvent> {
+    let start = events.first().map_or(0.0, |(time, _)| *time);
+    events
+        .into_iter()
+        .map(|(time, key_down)| KeyEvent {
+            time: (time - start) as f32,
+            key_down,
+        })
+        .collect()
+}
+
+/// one `time,state` event per line, `;` and tabs are accepted as separators,
+/// a header line, empty lines and lines starting with `#` are skipped
+pub fn parse_csv(text: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut events = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split([',', ';', '\t']);
+        let time = fields
+            .next()
+            .and_then(|time| time.trim().parse::<f64>().ok());
+        let key_down = fields.next().and_then(parse_state);
+        match (time, key_down) {
+            (Some(time), Some(key_down)) => events.push((time, key_down)),
+            // header
+            (None, _) if events.is_empty() => {}
+            _ => return Err(format!("Invalid key event at line {}: {}", i + 1, line)),
+        }
+    }
+    Ok(from_start(events))
+}
+
+// state stored as a boolean, a number or a string
+fn json_state(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(key_down) => Some(*key_down),
+        Value::Number(x) => Some(*x != 0.0),
+        Value::String(state) => parse_state(state),
+        _ => None,
+    }
+}
+
+fn json_event(value: &Value) -> Option<(f64, bool)> {
+    let (time, state) = match value {
+        Value::Array(pair) if pair.len() == 2 => (&pair[0], &pair[1]),
+        Value::Object(_) => (
+            ["time", "t", "timestamp"]
+                .iter()
+                .find_map(|key| value.get(key))?,
+            ["state", "key", "down"]
+                .iter()
+                .find_map(|key| value.get(key))?,
+        ),
+        _ => return None,
+    };
+    Some((time.as_f64()?, json_state(state)?))
+}
+
+/// array of `{"time": 0.12, "state": "down"}` objects or `[0.12, true]` pairs,
+/// optionally inside the `events` field of an object
+pub fn parse_json(text: &str) -> Result<Vec<KeyEvent>, String> {
+    let document = json::parse(text)?;
+    let events = document
+        .get("events")
+        .unwrap_or(&document)
+        .as_array()
+        .ok_or("Key events must be a JSON array")?;
+    let events = events
+        .iter()
+        .enumerate()
+        .map(|(i, event)| json_event(event).ok_or(format!("Invalid key event at index {}", i)))
+        .collect::<Result<Vec<(f64, bool)>, String>>()?;
+    Ok(from_start(events))
+}
+
+/// key down and key up runs between the events, repeated states are merged,
+/// nothing before the first key down and after the last key up is kept
+pub fn runs_from_events(events: &[KeyEvent]) -> Result<Vec<Run>, String> {
+    let mut runs: Vec<Run> = Vec::new();
+    let mut current: Option<KeyEvent> = None;
+    for event in events {
+        match current {
+            Some(last) if event.time < last.time => {
+                return Err(format!(
+                    "Key events are not in time order at {}s",
+                    event.time
+                ))
+            }
+            Some(last) if last.key_down == event.key_down => continue,
+            Some(last) => runs.push(Run {
+                key_down: last.key_down,
+                duration: event.time - last.time,
+            }),
+            None if !event.key_down => continue,
+            None => {}
+        }
+        current = Some(*event);
+    }
+    if runs.last().is_some_and(|run| !run.key_down) {
+        runs.pop();
+    }
+    Ok(runs)
+}
+
+/// decode the key presses with the same classification used for audio
+pub fn decode_events(events: &[KeyEvent]) -> Result<Transcript, String> {
+    decode_runs(&runs_from_events(events)?)
+}
+
+#[test]
+fn test_events() {
+    use crate::Letter;
+
+    // "ai e" at 100 ms a dot, with a header and a repeated state
+    let csv = "time,state\n0,down\n0.1,up\n0.2,down\n0.5,up\n0.8,down\n0.9,up\n\
+               1.0,down\n1.1,up\n1.1,up\n1.8,down\n1.9,up\n";
+    let events = parse_csv(csv).unwrap();
+    assert_eq!(runs_from_events(&events).unwrap().len(), 9);
+    let text = Letter::concat_text(decode_events(&events).unwrap().letters);
+    assert_eq!(String::from_utf8(text).unwrap(), "ai e");
+
+    let json = r#"{"events": [[0, "down"], {"t": 0.1, "key": 0}, [0.2, true], [0.5, false]]}"#;
+    let text = Letter::concat_text(decode_events(&parse_json(json).unwrap()).unwrap().letters);
+    assert_eq!(String::from_utf8(text).unwrap(), "a");
+
+    // epoch timestamps keep their milliseconds
+    let csv = "1697000000.0,down\n1697000000.1,up\n1697000000.2,down\n1697000000.5,up";
+    let text = Letter::concat_text(decode_events(&parse_csv(csv).unwrap()).unwrap().letters);
+    assert_eq!(String::from_utf8(text).unwrap(), "a");
+    let json = "[[1697000000.0, 1], [1697000000.1, 0], [1697000000.2, 1], [1697000000.5, 0]]";
+    let events = parse_json(json).unwrap();
+    assert!((events[1].time - 0.1).abs() < 1e-6);
+
+    assert!(parse_csv("0,down\n0.1,sideways").is_err());
+    assert!(runs_from_events(&parse_csv("1,down\n0.5,up").unwrap()).is_err());
+}
@@ -0,0 +1,29 @@
+use std::f32::consts::PI;
+
+/// amplitude of the `frequency` component inside `samples`,
+/// a full scale sine at that frequency gives ~1.0
+pub fn goertzel(samples: &[f32], frequency: f32, sample_rate: f32) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let coeff = 2.0 * f32::cos(2.0 * PI * frequency / sample_rate);
+    let (mut s1, mut s2) = (0_f32, 0_f32);
+    for sample in samples {
+        let s0 = sample + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    let power = (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0);
+    2.0 * power.sqrt() / samples.len() as f32
+}
+
+#[test]
+fn test_goertzel() {
+    use crate::polyphonia::{notable_notes, Amplitude, SAMPLE_RATE};
+
+    let wave = notable_notes::A4.float_wave(0.05, &Amplitude::Custom(1.0));
+    let on_tone = goertzel(&wave, 440.0, SAMPLE_RATE as f32);
+    let off_tone = goertzel(&wave, 880.0, SAMPLE_RATE as f32);
+    assert!((on_tone - 1.0).abs() < 0.05, "{}", on_tone);
+    assert!(off_tone < 0.05, "{}", off_tone);
+}
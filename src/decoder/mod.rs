@@ -0,0 +1,21 @@
+//public modules
+pub mod audio;
+pub mod events;
+pub mod goertzel;
+pub mod report;
+pub mod signal;
+pub mod skimmer;
+pub mod spectrum;
+pub mod stream;
+pub mod timing;
+
+// public uses
+pub use audio::{AudioDecoder, ToneTrack};
+pub use events::KeyEvent;
+pub use report::{CharacterReport, DecodeReport};
+pub use signal::SignalDecoder;
+pub use skimmer::{SkimResult, Skimmer};
+pub use stream::StreamDecoder;
+pub use timing::{
+    AdaptiveClassifier, Run, SpeedEstimate, SpeedTracker, TimingClassifier, Transcript,
+};
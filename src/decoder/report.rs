@@ -0,0 +1,209 @@
+use super::timing::{SpeedEstimate, Symbol};
+use crate::json;
+use crate::Letter;
+use std::str::FromStr;
+
+// symbols fitting their ideal length worse than this get alternative readings
+const AMBIGUOUS_FIT: f32 = 0.5;
+// at most 2^MAX_AMBIGUOUS alternative codes are tried for a single character
+const MAX_AMBIGUOUS: usize = 3;
+
+/// quality of a single decoded character
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterReport {
+    /// seconds from the start of the recording
+    pub time: f32,
+    /// decoded text, `*` when the code has no letter
+    pub text: String,
+    /// dots and dashes heard, `/` for a word space
+    pub morse: String,
+    /// tone level over the noise floor in dB, not available for spaces
+    pub snr: Option<f32>,
+    /// from 0.0 to 1.0, how close the timing is to the ideal 1:3 ratios
+    pub confidence: f32,
+    /// other letters readable swapping the most doubtful dots and dashes
+    pub alternatives: Vec<String>,
+}
+
+/// everything known about a decoded recording
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodeReport {
+    pub frequency: Option<f32>,
+    pub speed: Vec<SpeedEstimate>,
+    pub characters: Vec<CharacterReport>,
+}
+
+// how well `units` fits the `expected` length, 0.0 on the boundary with the other symbol
+fn fit(units: f32, expected: f32) -> f32 {
+    (1.0 - (units / expected).ln().abs() / (3_f32.ln() / 2.0)).clamp(0.0, 1.0)
+}
+
+fn letter_text(code: &str) -> Option<&'static str> {
+    Letter::from_str(code).ok().map(|Letter(text, _)| text)
+}
+
+// every valid letter readable flipping the ambiguous symbols, except `code` itself
+fn alternatives(code: &str, ambiguous: &[usize]) -> Vec<String> {
+    let ambiguous = &ambiguous[..ambiguous.len().min(MAX_AMBIGUOUS)];
+    let mut output: Vec<String> = Vec::new();
+    for mask in 1..(1_usize << ambiguous.len()) {
+        let mut symbols: Vec<char> = code.chars().collect();
+        for (bit, index) in ambiguous.iter().enumerate() {
+            if mask & (1 << bit) != 0 {
+                symbols[*index] = if symbols[*index] == '.' { '-' } else { '.' };
+            }
+        }
+        let flipped: String = symbols.into_iter().collect();
+        if let Some(text) = letter_text(&flipped) {
+            if !output.iter().any(|x| x == text) {
+                output.push(text.into());
+            }
+        }
+    }
+    output
+}
+
+// character being collected symbol by symbol
+#[derive(Default)]
+struct PendingCharacter {
+    code: String,
+    fits: Vec<f32>,
+    ambiguous: Vec<usize>,
+    start: f32,
+    level_sum: f32,
+}
+
+impl PendingCharacter {
+    fn push_mark(&mut self, symbol: &Symbol, level: f32) {
+        if self.code.is_empty() {
+            self.start = symbol.time;
+        }
+        let expected = if symbol.text == "." { 1.0 } else { 3.0 };
+        let symbol_fit = fit(symbol.units(), expected);
+        if symbol_fit < AMBIGUOUS_FIT {
+            self.ambiguous.push(self.code.len());
+        }
+        self.fits.push(symbol_fit);
+        self.code.push_str(symbol.text);
+        self.level_sum += level;
+    }
+
+    fn finish(&mut self, noise: f32, offset: f32) -> Option<CharacterReport> {
+        if self.code.is_empty() {
+            return None;
+        }
+        let pending = std::mem::take(self);
+        let mean_level = pending.level_sum / pending.code.len() as f32;
+        Some(CharacterReport {
+            time: offset + pending.start,
+            text: letter_text(&pending.code).unwrap_or("*").into(),
+            snr: Some(20.0 * (mean_level / noise.max(f32::EPSILON)).log10()),
+            confidence: pending.fits.iter().copied().fold(1.0, f32::min),
+            alternatives: alternatives(&pending.code, &pending.ambiguous),
+            morse: pending.code,
+        })
+    }
+}
+
+impl DecodeReport {
+    /// group the symbols into characters, `levels` holds the mean tone level
+    /// of every symbol, `noise` the level of the silence and `offset` the time
+    /// of the first key down
+    pub fn characters(
+        symbols: &[Symbol],
+        levels: &[f32],
+        noise: f32,
+        offset: f32,
+    ) -> Vec<CharacterReport> {
+        let mut output: Vec<CharacterReport> = Vec::new();
+        let mut pending = PendingCharacter::default();
+        for (symbol, level) in symbols.iter().zip(levels) {
+            match symbol.text {
+                "." | "-" => pending.push_mark(symbol, *level),
+                "" => pending.fits.push(fit(symbol.units(), 1.0)),
+                gap => {
+                    output.extend(pending.finish(noise, offset));
+                    if gap.trim() == "/" {
+                        output.push(CharacterReport {
+                            time: offset + symbol.time,
+                            text: " ".into(),
+                            morse: "/".into(),
+                            snr: None,
+                            confidence: 1.0,
+                            alternatives: Vec::new(),
+                        });
+                    }
+                }
+            }
+        }
+        output.extend(pending.finish(noise, offset));
+        output
+    }
+
+    pub fn text(&self) -> String {
+        self.characters.iter().map(|ch| ch.text.as_str()).collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        json::object([
+            (
+                "frequency",
+                self.frequency.map_or("null".into(), json::number),
+            ),
+            (
+                "speed",
+                json::array(self.speed.iter().map(|estimate| {
+                    json::object([
+                        ("time", json::number(estimate.time)),
+                        ("wpm", json::number(estimate.wpm)),
+                    ])
+                })),
+            ),
+            ("text", json::string(&self.text())),
+            (
+                "characters",
+                json::array(self.characters.iter().map(|ch| {
+                    json::object([
+                        ("time", json::number(ch.time)),
+                        ("text", json::string(&ch.text)),
+                        ("morse", json::string(&ch.morse)),
+                        ("snr", ch.snr.map_or("null".into(), json::number)),
+                        ("confidence", json::number(ch.confidence)),
+                        (
+                            "alternatives",
+                            json::array(ch.alternatives.iter().map(|x| json::string(x))),
+                        ),
+                    ])
+                })),
+            ),
+        ])
+    }
+}
+
+#[test]
+fn test_characters() {
+    use super::timing::{AdaptiveClassifier, Run};
+
+    // "ea" with the dash of the a too short to be sure
+    let runs = [
+        (true, 1.0),
+        (false, 3.0),
+        (true, 1.0),
+        (false, 1.0),
+        (true, 1.9),
+    ]
+    .map(|(key_down, units)| Run {
+        key_down,
+        duration: units * 0.1,
+    });
+    let (symbols, _) = AdaptiveClassifier::new().symbols(&runs);
+    let characters = DecodeReport::characters(&symbols, &[1.0; 5], 0.01, 2.0);
+    assert_eq!(characters.len(), 2);
+    assert_eq!(characters[0].text, "e");
+    assert!((characters[0].time - 2.0).abs() < 1e-6);
+    assert!((characters[0].snr.unwrap() - 40.0).abs() < 1e-3);
+    assert!(characters[0].confidence > 0.9);
+    assert!(characters[1].confidence < AMBIGUOUS_FIT);
+    let other = if characters[1].text == "a" { "i" } else { "a" };
+    assert_eq!(characters[1].alternatives, vec![other.to_string()]);
+}
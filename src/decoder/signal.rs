@@ -0,0 +1,220 @@
+use super::report::DecodeReport;
+use super::timing::{decode_runs, runs_from_states, AdaptiveClassifier, Run, Transcript};
+
+/// decode morse from any sampled on/off signal: light sensors, logic captures
+/// or voltages, values are binarized with a threshold and a dead band
+#[derive(Debug, Clone, Copy)]
+pub struct SignalDecoder {
+    /// seconds between two samples
+    pub interval: f32,
+    /// level switching the key, halfway between the low and the high level when not set
+    pub threshold: Option<f32>,
+    /// width of the dead band around the threshold, as a fraction of the signal range,
+    /// the key changes state only when the signal crosses the whole band
+    pub hysteresis: f32,
+    /// low values mean key down, e.g. a sensor covered while the key is pressed
+    pub inverted: bool,
+}
+
+impl SignalDecoder {
+    pub fn new(interval: f32) -> Self {
+        SignalDecoder {
+            interval,
+            threshold: None,
+            hysteresis: 0.1,
+            inverted: false,
+        }
+    }
+
+    // values with the key down always higher
+    fn oriented(&self, values: &[f32]) -> Vec<f32> {
+        let sign = if self.inverted { -1.0 } else { 1.0 };
+        values.iter().map(|value| value * sign).collect()
+    }
+
+    // typical key up and key down levels, ignoring isolated spikes
+    fn range(values: &[f32]) -> Option<(f32, f32)> {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let low = *sorted.get(sorted.len() / 10)?;
+        let high = *sorted.get(sorted.len() * 9 / 10)?;
+        Some((low, high))
+    }
+
+    /// levels the signal must fall below to release the key and rise above
+    /// to press it, in the orientation of `values`
+    pub fn band(&self, values: &[f32]) -> Option<(f32, f32)> {
+        let oriented = self.oriented(values);
+        let (low, high) = Self::range(&oriented)?;
+        let threshold = match self.threshold {
+            Some(threshold) if self.inverted => -threshold,
+            Some(threshold) => threshold,
+            None => (low + high) / 2.0,
+        };
+        let half_width = (high - low) * self.hysteresis / 2.0;
+        let (bottom, top) = (threshold - half_width, threshold + half_width);
+        Some(if self.inverted {
+            (-top, -bottom)
+        } else {
+            (bottom, top)
+        })
+    }
+
+    /// key state at every sample
+    pub fn key_states(&self, values: &[f32]) -> Vec<bool> {
+        let oriented = self.oriented(values);
+        let Some((low, high)) = self.band(values) else {
+            return Vec::new();
+        };
+        let (release, press) = if self.inverted {
+            (-high, -low)
+        } else {
+            (low, high)
+        };
+        let mut key_down = false;
+        oriented
+            .iter()
+            .map(|value| {
+                if key_down && *value < release {
+                    key_down = false;
+                } else if !key_down && *value > press {
+                    key_down = true;
+                }
+                key_down
+            })
+            .collect()
+    }
+
+    pub fn runs(&self, values: &[f32]) -> Vec<Run> {
+        runs_from_states(self.key_states(values), self.interval)
+    }
+
+    /// decoded letters with the speed of the sender over time
+    pub fn decode(&self, values: &[f32]) -> Result<Transcript, String> {
+        decode_runs(&self.runs(values))
+    }
+
+    /// timing, signal to noise ratio and confidence of every character
+    pub fn report(&self, values: &[f32]) -> DecodeReport {
+        let oriented = self.oriented(values);
+        let states = self.key_states(values);
+        let idle: Vec<f32> = oriented
+            .iter()
+            .zip(&states)
+            .filter(|(_, key_down)| !**key_down)
+            .map(|(value, _)| *value)
+            .collect();
+        let idle_level = idle.iter().sum::<f32>() / idle.len().max(1) as f32;
+        // mean distance of the idle samples from their level
+        let noise =
+            idle.iter().map(|v| (v - idle_level).abs()).sum::<f32>() / idle.len().max(1) as f32;
+        let first = states.iter().position(|key_down| *key_down).unwrap_or(0);
+
+        let runs = runs_from_states(states, self.interval);
+        let mut index = first;
+        let levels: Vec<f32> = runs
+            .iter()
+            .map(|run| {
+                let samples = ((run.duration / self.interval).round() as usize).max(1);
+                let end = (index + samples).min(oriented.len());
+                let level = oriented[index..end]
+                    .iter()
+                    .map(|value| value - idle_level)
+                    .sum::<f32>()
+                    / samples as f32;
+                index = end;
+                level
+            })
+            .collect();
+        let (symbols, speed) = AdaptiveClassifier::new().symbols(&runs);
+        DecodeReport {
+            frequency: None,
+            speed,
+            characters: DecodeReport::characters(
+                &symbols,
+                &levels,
+                noise,
+                first as f32 * self.interval,
+            ),
+        }
+    }
+}
+
+/// samples of a CSV time series, one per line: a single value or `time,value`,
+/// in the second case the sample interval is the mean step of the time column,
+/// a header line and empty lines are skipped
+pub fn parse_series(text: &str) -> Result<(Vec<f32>, Option<f32>), String> {
+    let mut values: Vec<f32> = Vec::new();
+    let mut times: Vec<f32> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Option<Vec<f32>> = line
+            .split([',', ';', '\t'])
+            .map(|field| field.trim().parse::<f32>().ok())
+            .collect();
+        match fields.as_deref() {
+            Some([value]) => values.push(*value),
+            Some([time, .., value]) => {
+                times.push(*time);
+                values.push(*value);
+            }
+            // header
+            None if values.is_empty() => {}
+            _ => return Err(format!("Invalid sample at line {}: {}", i + 1, line)),
+        }
+    }
+    let interval = match (times.first(), times.last()) {
+        (Some(first), Some(last)) if times.len() == values.len() && times.len() > 1 => {
+            Some((last - first) / (times.len() - 1) as f32)
+        }
+        _ => None,
+    };
+    Ok((values, interval))
+}
+
+#[test]
+fn test_signal() {
+    use crate::keying::{keying, Key};
+    use crate::wav::sample::Dither;
+    use crate::Letter;
+    use std::str::FromStr;
+
+    // "sos" from a photodiode: 0.8 V in the dark, 0.2 V lit, 50 ms a dot sampled every ms
+    let letters = "sos"
+        .chars()
+        .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+    let mut noise = Dither::new(3);
+    let mut csv = String::from("time,volts\n");
+    let mut time = 0;
+    for key in [Key::Up(5)].iter().chain(&keying(letters)) {
+        let (units, level) = match key {
+            Key::Down(units) => (units, 0.2),
+            Key::Up(units) => (units, 0.8),
+        };
+        for _ in 0..units * 50 {
+            let value = level + 0.2 * noise.next_offset() as f32;
+            csv.push_str(&format!("{},{}\n", time as f32 / 1000.0, value));
+            time += 1;
+        }
+    }
+    let (values, interval) = parse_series(&csv).unwrap();
+    let interval = interval.unwrap();
+    assert!((interval - 0.001).abs() < 1e-5);
+
+    let mut decoder = SignalDecoder::new(interval);
+    decoder.inverted = true;
+    let text = Letter::concat_text(decoder.decode(&values).unwrap().letters);
+    assert_eq!(String::from_utf8(text).unwrap(), "sos");
+    let report = decoder.report(&values);
+    assert_eq!(report.text(), "sos");
+    assert!((report.characters[0].time - 0.25).abs() < 0.01);
+    assert!(report.characters[0].snr.unwrap() > 10.0);
+
+    // without a dead band the noise chatters around the threshold
+    decoder.hysteresis = 0.0;
+    decoder.threshold = Some(0.3);
+    assert!(decoder.runs(&values).len() > 2 * SignalDecoder::new(interval).runs(&values).len());
+}
@@ -0,0 +1,155 @@
+use super::audio::{AudioDecoder, ToneTrack};
+use super::spectrum::average_spectrum;
+use super::timing::{runs_from_states, AdaptiveClassifier};
+use crate::Letter;
+use std::str::FromStr;
+
+// seconds of audio, at least, in the spectrum used to find the signals
+const SPECTRUM_DURATION: f32 = 0.05;
+
+/// a signal found in the passband and its decoded text
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkimResult {
+    /// pitch of the signal in Hz
+    pub frequency: f32,
+    /// seconds from the start of the recording to the first key down
+    pub start_time: f32,
+    /// decoded text, `*` marks codes without a letter
+    pub text: String,
+}
+
+/// finds every CW signal in a recording and decodes each one on its own
+#[derive(Debug, Clone, Copy)]
+pub struct Skimmer {
+    pub min_frequency: f32,
+    pub max_frequency: f32,
+    /// signals closer than this many Hz are considered the same one
+    pub min_separation: f32,
+    /// weakest signal kept, relative to the strongest one
+    pub min_level: f32,
+    /// length in seconds of the blocks analyzed to follow every signal,
+    /// longer blocks separate closer signals
+    pub block_duration: f32,
+}
+
+impl Default for Skimmer {
+    fn default() -> Self {
+        Skimmer {
+            min_frequency: 300.0,
+            max_frequency: 1200.0,
+            min_separation: 80.0,
+            min_level: 0.15,
+            block_duration: 0.01,
+        }
+    }
+}
+
+impl Skimmer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// frequencies of the signals in the passband, strongest first
+    pub fn find_signals(&self, samples: &[f32], sample_rate: u32) -> Vec<f32> {
+        let size = ((sample_rate as f32 * SPECTRUM_DURATION) as usize).next_power_of_two();
+        let bin_width = sample_rate as f32 / size as f32;
+        let mut padded = samples.to_vec();
+        if padded.len() < size {
+            padded.resize(size, 0.0);
+        }
+        let spectrum = average_spectrum(&padded, size);
+        let first = ((self.min_frequency / bin_width).ceil() as usize).max(1);
+        let last = ((self.max_frequency / bin_width).floor() as usize)
+            .min(spectrum.len().saturating_sub(2));
+        if first > last {
+            return Vec::new();
+        }
+        let mut peaks: Vec<(f32, f32)> = (first..=last)
+            .filter(|i| spectrum[*i] > spectrum[i - 1] && spectrum[*i] >= spectrum[i + 1])
+            .map(|i| (i as f32 * bin_width, spectrum[i]))
+            .collect();
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let loudest = peaks.first().map_or(0.0, |(_, level)| *level);
+
+        let mut signals: Vec<f32> = Vec::new();
+        for (frequency, level) in peaks {
+            if level < loudest * self.min_level {
+                break;
+            }
+            if signals
+                .iter()
+                .all(|signal| (signal - frequency).abs() >= self.min_separation)
+            {
+                signals.push(frequency);
+            }
+        }
+        signals
+    }
+
+    /// decode every signal found, sorted by start time
+    pub fn skim(&self, samples: &[f32], sample_rate: u32) -> Vec<SkimResult> {
+        let mut results: Vec<SkimResult> = self
+            .find_signals(samples, sample_rate)
+            .into_iter()
+            .filter_map(|frequency| {
+                let decoder = AudioDecoder {
+                    frequency: Some(frequency),
+                    block_duration: self.block_duration,
+                    ..Default::default()
+                };
+                let states = decoder.key_states(samples, sample_rate, &ToneTrack::fixed(frequency));
+                let start = states.iter().position(|key_down| *key_down)?;
+                let runs = runs_from_states(states, decoder.step(sample_rate));
+                let (morse, _) = AdaptiveClassifier::new().classify(&runs);
+                let text = morse
+                    .split_whitespace()
+                    .map(|code| Letter::from_str(code).map_or("*", |Letter(text, _)| text))
+                    .collect();
+                Some(SkimResult {
+                    frequency,
+                    start_time: start as f32 * decoder.step(sample_rate),
+                    text,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        results
+    }
+}
+
+#[test]
+fn test_pileup() {
+    use crate::keying::{keying, render};
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    // three stations calling at the same time, at different speeds and volumes
+    let stations = [
+        ("cq de ik2abc", 500.0, 0.0, 0.06, Amplitude::Medium),
+        ("de dl1xyz k", 750.0, 0.4, 0.05, Amplitude::Low),
+        ("test f5aa", 1000.0, 1.1, 0.07, Amplitude::High),
+    ];
+    let mut samples: Vec<f32> = Vec::new();
+    for (message, pitch, start, dot, volume) in &stations {
+        let letters = message
+            .chars()
+            .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+        let offset = (start * SAMPLE_RATE as f32) as usize;
+        let wave = render(&keying(letters), &Note(*pitch), *dot, volume);
+        if samples.len() < offset + wave.len() {
+            samples.resize(offset + wave.len(), 0.0);
+        }
+        for (i, x) in wave.iter().enumerate() {
+            samples[offset + i] += x / stations.len() as f32;
+        }
+    }
+
+    let results = Skimmer::new().skim(&samples, SAMPLE_RATE);
+    assert_eq!(results.len(), 3, "{:?}", results);
+    for (result, (message, pitch, start, _, _)) in results.iter().zip(&stations) {
+        assert_eq!(&result.text, message);
+        assert!((result.frequency - pitch).abs() < 15.0, "{:?}", result);
+        assert!((result.start_time - start).abs() < 0.05, "{:?}", result);
+    }
+    // too slow a sample rate for any tone of the passband
+    assert!(Skimmer::new().skim(&samples, 40).is_empty());
+}
@@ -0,0 +1,118 @@
+use std::f32::consts::PI;
+
+/// in place radix-2 FFT, `re` and `im` must have the same power of two length
+pub fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    assert!(
+        n.is_power_of_two() && im.len() == n,
+        "fft needs a power of two length"
+    );
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = (f32::cos(angle * k as f32), f32::sin(angle * k as f32));
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// magnitude of the first half of the spectrum of `samples` with a Hann window,
+/// `samples` must have a power of two length
+pub fn magnitude_spectrum(samples: &[f32]) -> Vec<f32> {
+    let n = samples.len();
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| x * (0.5 - 0.5 * f32::cos(2.0 * PI * i as f32 / n as f32)))
+        .collect();
+    let mut im = vec![0_f32; n];
+    fft(&mut re, &mut im);
+    re.iter()
+        .zip(im.iter())
+        .take(n / 2)
+        .map(|(re, im)| (re * re + im * im).sqrt())
+        .collect()
+}
+
+/// mean magnitude spectrum of windows of `size` samples overlapping by half
+pub fn average_spectrum(samples: &[f32], size: usize) -> Vec<f32> {
+    let mut total = vec![0_f32; size / 2];
+    // a single sample has no spectrum
+    if size < 2 {
+        return total;
+    }
+    let mut windows = 0;
+    for start in (0..samples.len().saturating_sub(size - 1)).step_by(size / 2) {
+        for (sum, x) in total
+            .iter_mut()
+            .zip(magnitude_spectrum(&samples[start..start + size]))
+        {
+            *sum += x;
+        }
+        windows += 1;
+    }
+    if windows > 0 {
+        total.iter_mut().for_each(|x| *x /= windows as f32);
+    }
+    total
+}
+
+/// frequency and magnitude of the strongest bin between `min_frequency` and `max_frequency`,
+/// refined with a parabola through the nearby bins
+pub fn peak(
+    spectrum: &[f32],
+    bin_width: f32,
+    min_frequency: f32,
+    max_frequency: f32,
+) -> Option<(f32, f32)> {
+    let first = ((min_frequency / bin_width).ceil() as usize).max(1);
+    let last = ((max_frequency / bin_width).floor() as usize).min(spectrum.len().saturating_sub(2));
+    let bin = (first..=last).max_by(|a, b| spectrum[*a].total_cmp(&spectrum[*b]))?;
+    let (left, center, right) = (spectrum[bin - 1], spectrum[bin], spectrum[bin + 1]);
+    let denominator = left - 2.0 * center + right;
+    let offset = if denominator.abs() > f32::EPSILON {
+        (0.5 * (left - right) / denominator).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    Some(((bin as f32 + offset) * bin_width, center))
+}
+
+#[test]
+fn test_peak() {
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    let wave = Note(700.0).float_wave(0.5, &Amplitude::Medium);
+    let spectrum = average_spectrum(&wave, 4096);
+    let bin_width = SAMPLE_RATE as f32 / 4096.0;
+    let (frequency, _) = peak(&spectrum, bin_width, 300.0, 1200.0).unwrap();
+    assert!((frequency - 700.0).abs() < 2.0, "{}", frequency);
+    assert!(peak(&spectrum, bin_width, 800.0, 1200.0).unwrap().0 > 800.0);
+    // audio sampled too slowly for a window of two samples
+    assert!(average_spectrum(&wave, 1).is_empty());
+    assert_eq!(peak(&average_spectrum(&wave, 1), 8.0, 300.0, 1200.0), None);
+}
@@ -0,0 +1,284 @@
+use super::audio::{AudioDecoder, ToneTrack, MIN_LEVEL};
+use super::goertzel::goertzel;
+use super::timing::{AdaptiveClassifier, Run, SpeedTracker};
+use crate::dsp::envelope::time_constant;
+use crate::dsp::{Chain, EnvelopeFollower, Stage};
+use crate::Letter;
+use std::mem;
+use std::str::FromStr;
+
+// seconds of audio listened to before picking the tone, when not given,
+// listening goes on while the audio is only noise
+const DETECT_DURATION: f32 = 1.0;
+// seconds the peak and the noise floor take to follow a change of level
+const LEVEL_TIME: f32 = 3.0;
+// the peak must be this many times above the noise floor to key down
+const MIN_SNR: f32 = 3.0;
+// and this many times to lock on a tone, so that clicks and hum don't
+const LOCK_SNR: f32 = 10.0;
+
+/// decode audio pushed a piece at a time, letters are returned as soon as
+/// the gap after them is long enough and memory does not grow with the input
+pub struct StreamDecoder {
+    settings: AudioDecoder,
+    classifier: AdaptiveClassifier,
+    sample_rate: u32,
+    // audio waiting for a keyed tone to be found, then the rest of an incomplete block
+    pending: Vec<f32>,
+    // samples to wait for before trying to find the tone again
+    skipped: usize,
+    tone: Option<f32>,
+    chain: Chain,
+    peak: EnvelopeFollower,
+    // noise level, falls at once and rises slowly
+    floor: f32,
+    floor_rise: f32,
+    // run in progress, none before the first key down
+    run: Option<Run>,
+    // first runs, kept until the speed can be guessed
+    warmup: Vec<Run>,
+    tracker: Option<SpeedTracker>,
+    // symbols of the letter being received
+    code: String,
+    word_gap: bool,
+}
+
+impl StreamDecoder {
+    pub fn new(settings: AudioDecoder, sample_rate: u32) -> Self {
+        let blocks_per_second = 1.0 / settings.step(sample_rate);
+        StreamDecoder {
+            settings,
+            classifier: AdaptiveClassifier::new(),
+            sample_rate,
+            pending: Vec::new(),
+            skipped: 0,
+            tone: None,
+            chain: Chain::new(),
+            peak: EnvelopeFollower::new(blocks_per_second, 0.0, LEVEL_TIME),
+            floor: 0.0,
+            floor_rise: time_constant(blocks_per_second, LEVEL_TIME),
+            run: None,
+            warmup: Vec::new(),
+            tracker: None,
+            code: String::new(),
+            word_gap: false,
+        }
+    }
+
+    /// pitch of the tone being decoded, once known
+    pub fn frequency(&self) -> Option<f32> {
+        self.tone
+    }
+
+    /// feed more samples, returns the text recognized in the meantime
+    pub fn push(&mut self, samples: &[f32]) -> String {
+        let mut text = String::new();
+        self.pending.extend_from_slice(samples);
+        if self.tone.is_none() {
+            let window = (DETECT_DURATION * self.sample_rate as f32) as usize;
+            if self.pending.len() < window + self.skipped {
+                return text;
+            }
+            match self.detect(&self.pending[self.pending.len() - window..]) {
+                (tone, floor, true) => self.start(tone, floor),
+                // nothing keyed yet, the last window is kept in case the keying
+                // starts in it and the next one is tried half a window later
+                _ => {
+                    self.pending.drain(..self.pending.len() - window);
+                    self.skipped = window / 2;
+                    return text;
+                }
+            }
+        }
+        let block_size = self.settings.block_size(self.sample_rate);
+        let mut pending = mem::take(&mut self.pending);
+        let mut blocks = pending.chunks_exact_mut(block_size);
+        for block in &mut blocks {
+            self.block(block, &mut text);
+        }
+        self.pending = blocks.into_remainder().to_vec();
+        text
+    }
+
+    /// end of the audio, returns the letters still waiting for a gap
+    pub fn finish(&mut self) -> String {
+        let mut text = String::new();
+        if self.tone.is_none() {
+            let (tone, floor, _) = self.detect(&self.pending);
+            self.start(tone, floor);
+            text = self.push(&[]);
+        }
+        if let Some(run) = self.run.take().filter(|run| run.key_down) {
+            self.end_run(run, &mut text);
+        }
+        if self.tracker.is_none() {
+            let runs = mem::take(&mut self.warmup);
+            self.tracker = SpeedTracker::from_runs(self.classifier, &runs);
+            for run in runs {
+                self.classify(run, &mut text);
+            }
+        }
+        self.flush_letter(&mut text);
+        text
+    }
+
+    // strongest tone of `samples`, its noise floor, and whether it is keyed
+    // clearly above the noise rather than a steady hum or a noise peak
+    fn detect(&self, samples: &[f32]) -> (f32, f32, bool) {
+        let detector = AudioDecoder {
+            track_drift: false,
+            ..self.settings
+        };
+        let Some(track) = detector.tone_track(samples, self.sample_rate) else {
+            return (self.settings.min_frequency, 0.0, false);
+        };
+        let mut levels: Vec<f32> = samples
+            .chunks_exact(self.settings.block_size(self.sample_rate))
+            .map(|block| goertzel(block, track.frequency, self.sample_rate as f32))
+            .collect();
+        levels.sort_by(f32::total_cmp);
+        let loudest = levels.last().copied().unwrap_or_default();
+        // the lower quartile, in the gaps of a keyed tone and steady in noise
+        let floor = levels.get(levels.len() / 4).copied().unwrap_or_default();
+        let keyed = loudest >= MIN_LEVEL && loudest > floor * LOCK_SNR;
+        (
+            track.frequency,
+            floor,
+            keyed || self.settings.frequency.is_some(),
+        )
+    }
+
+    // lock on the tone, starting from the noise heard so far,
+    // and set up the conditioning stages
+    fn start(&mut self, tone: f32, floor: f32) {
+        self.tone = Some(tone);
+        self.floor = floor;
+        self.chain = self
+            .settings
+            .conditioning(self.sample_rate, &ToneTrack::fixed(tone));
+    }
+
+    fn block(&mut self, block: &mut [f32], text: &mut String) {
+        if !self.chain.is_empty() {
+            self.chain.process_all(block);
+        }
+        let tone = self.tone.unwrap_or(self.settings.min_frequency);
+        let level = goertzel(block, tone, self.sample_rate as f32);
+        let peak = self.peak.process(level);
+        self.floor = if level > self.floor {
+            self.floor + (level - self.floor) * self.floor_rise
+        } else {
+            level
+        };
+        let floor = self.floor;
+        let key_down =
+            peak >= MIN_LEVEL && peak > floor * MIN_SNR && level > floor + (peak - floor) / 2.0;
+
+        let step = self.settings.step(self.sample_rate);
+        match self.run.as_mut() {
+            Some(run) if run.key_down == key_down => run.duration += step,
+            None if !key_down => {}
+            _ => {
+                let next = Run {
+                    key_down,
+                    duration: step,
+                };
+                if let Some(run) = self.run.replace(next) {
+                    self.end_run(run, text);
+                }
+            }
+        }
+        // a letter is complete as soon as the gap is longer than the ones inside letters
+        if let (Some(run), Some(tracker)) = (&self.run, &self.tracker) {
+            if !run.key_down && run.duration >= tracker.letter_gap() {
+                self.flush_letter(text);
+            }
+        }
+    }
+
+    fn end_run(&mut self, run: Run, text: &mut String) {
+        if self.tracker.is_some() {
+            return self.classify(run, text);
+        }
+        self.warmup.push(run);
+        if self.warmup.len() >= self.classifier.window {
+            let runs = mem::take(&mut self.warmup);
+            self.tracker = SpeedTracker::from_runs(self.classifier, &runs);
+            for run in runs {
+                self.classify(run, text);
+            }
+        }
+    }
+
+    fn classify(&mut self, run: Run, text: &mut String) {
+        let Some(tracker) = self.tracker.as_mut() else {
+            return;
+        };
+        match tracker.push(&run) {
+            "" => {}
+            " " => self.flush_letter(text),
+            " / " => {
+                self.flush_letter(text);
+                self.word_gap = true;
+            }
+            symbol => self.code.push_str(symbol),
+        }
+    }
+
+    // write the letter received so far, `*` when the code is unknown
+    fn flush_letter(&mut self, text: &mut String) {
+        if self.code.is_empty() {
+            return;
+        }
+        if mem::take(&mut self.word_gap) {
+            text.push(' ');
+        }
+        text.push_str(Letter::from_str(&self.code).map_or("*", |Letter(letter, _)| letter));
+        self.code.clear();
+    }
+}
+
+#[test]
+fn test_stream() {
+    use crate::keying::{keying, render};
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    let letters = "cq cq de k1abc k"
+        .chars()
+        .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+    let samples = render(&keying(letters), &Note(650.0), 0.06, &Amplitude::Medium);
+    let mut decoder = StreamDecoder::new(AudioDecoder::new(), SAMPLE_RATE);
+    let mut text = String::new();
+    let half = samples.len() / 2;
+    for chunk in samples[..half].chunks(1000) {
+        text.push_str(&decoder.push(chunk));
+    }
+    // letters come out while the audio is still flowing
+    assert!(text.starts_with("cq cq"), "{:?}", text);
+    for chunk in samples[half..].chunks(1000) {
+        text.push_str(&decoder.push(chunk));
+    }
+    text.push_str(&decoder.finish());
+    assert_eq!(text, "cq cq de k1abc k");
+    assert!((decoder.frequency().unwrap() - 650.0).abs() < 5.0);
+
+    // a live input starting with a few seconds of hum waits for the keying
+    let hum = |t: usize| {
+        0.02 * (2.0 * std::f32::consts::PI * 1100.0 * t as f32 / SAMPLE_RATE as f32).sin()
+    };
+    let start = 3 * SAMPLE_RATE as usize;
+    let live: Vec<f32> = (0..start + samples.len())
+        .map(|t| hum(t) + t.checked_sub(start).map_or(0.0, |t| samples[t]))
+        .collect();
+    let mut decoder = StreamDecoder::new(AudioDecoder::new(), SAMPLE_RATE);
+    let mut text = String::new();
+    for chunk in live.chunks(1000) {
+        text.push_str(&decoder.push(chunk));
+        if decoder.frequency().is_none() {
+            assert!(text.is_empty());
+        }
+    }
+    text.push_str(&decoder.finish());
+    assert!((decoder.frequency().unwrap() - 650.0).abs() < 5.0);
+    assert_eq!(text, "cq cq de k1abc k");
+}
@@ -0,0 +1,398 @@
+use crate::{morse_alphabet, Letter};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+// a PARIS word lasts 50 dots, so wpm = 60 / (50 * dot)
+const PARIS_DOT_SECONDS: f32 = 1.2;
+
+/// words per minute sent with the given dot length in seconds
+pub fn wpm(dot: f32) -> f32 {
+    PARIS_DOT_SECONDS / dot
+}
+
+/// stretch of time, in seconds, with the key always in the same state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    pub key_down: bool,
+    pub duration: f32,
+}
+
+/// group consecutive equal states sampled every `step` seconds into runs,
+/// silence before the first and after the last key down is dropped
+pub fn runs_from_states<I: IntoIterator<Item = bool>>(states: I, step: f32) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    for key_down in states {
+        match runs.last_mut() {
+            Some(run) if run.key_down == key_down => run.duration += step,
+            None if !key_down => {}
+            _ => runs.push(Run {
+                key_down,
+                duration: step,
+            }),
+        }
+    }
+    if runs.last().is_some_and(|run| !run.key_down) {
+        runs.pop();
+    }
+    runs
+}
+
+// centers of the two groups of `values` found with k-means
+fn two_means(values: &[f32]) -> (f32, f32) {
+    let mut low = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let mut high = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    for _ in 0..16 {
+        let (mut sum_low, mut len_low, mut sum_high, mut len_high) = (0_f32, 0, 0_f32, 0);
+        for value in values {
+            if (value - low).abs() <= (value - high).abs() {
+                sum_low += value;
+                len_low += 1;
+            } else {
+                sum_high += value;
+                len_high += 1;
+            }
+        }
+        if len_low > 0 {
+            low = sum_low / len_low as f32;
+        }
+        if len_high > 0 {
+            high = sum_high / len_high as f32;
+        }
+    }
+    (low, high)
+}
+
+/// length of a dot guessed from the key down durations,
+/// gaps are used when every symbol has the same length
+pub fn estimate_dot(runs: &[Run]) -> Option<f32> {
+    let marks: Vec<f32> = runs
+        .iter()
+        .filter(|run| run.key_down)
+        .map(|run| run.duration)
+        .collect();
+    if marks.is_empty() {
+        return None;
+    }
+    let (short, long) = two_means(&marks);
+    if long > short * 2.0 {
+        return Some(short);
+    }
+    let shortest_gap = runs
+        .iter()
+        .filter(|run| !run.key_down)
+        .map(|run| run.duration)
+        .fold(f32::INFINITY, f32::min);
+    if short > shortest_gap * 2.0 {
+        // only dashes
+        Some(short / 3.0)
+    } else {
+        Some(short)
+    }
+}
+
+/// turns runs into morse symbols using the 1:3:7 ratios of a dot length
+#[derive(Debug, Clone, Copy)]
+pub struct TimingClassifier {
+    pub dot: f32,
+}
+
+impl TimingClassifier {
+    pub fn new(dot: f32) -> Self {
+        TimingClassifier { dot }
+    }
+
+    pub fn from_runs(runs: &[Run]) -> Option<Self> {
+        estimate_dot(runs).map(Self::new)
+    }
+
+    /// text representation of a run: `.`, `-`, nothing between symbols,
+    /// ` ` between letters and ` / ` between words
+    pub fn symbol(&self, run: &Run) -> &'static str {
+        let units = run.duration / self.dot;
+        match (run.key_down, units) {
+            (true, units) if units < 2.0 => ".",
+            (true, _) => "-",
+            (false, units) if units < 2.0 => "",
+            (false, units) if units < 5.0 => " ",
+            (false, _) => " / ",
+        }
+    }
+
+    /// morse text in the same notation accepted by `Letter::from_str`
+    pub fn to_morse(&self, runs: &[Run]) -> String {
+        runs.iter().map(|run| self.symbol(run)).collect()
+    }
+
+    pub fn decode(&self, runs: &[Run]) -> Result<Vec<Letter<'static>>, String> {
+        self.to_morse(runs)
+            .split_whitespace()
+            .map(Letter::from_str)
+            .collect()
+    }
+}
+
+/// a run with its meaning and the dot length used to classify it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Symbol {
+    pub run: Run,
+    /// seconds from the first key down
+    pub time: f32,
+    pub dot: f32,
+    /// `.`, `-`, nothing between symbols, ` ` between letters and ` / ` between words
+    pub text: &'static str,
+}
+
+impl Symbol {
+    /// length of the run in dots
+    pub fn units(&self) -> f32 {
+        self.run.duration / self.dot
+    }
+}
+
+/// speed of the sender starting from `time` seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedEstimate {
+    pub time: f32,
+    pub wpm: f32,
+}
+
+/// decoded letters with the speed changes found along the way
+#[derive(Debug, Default)]
+pub struct Transcript {
+    pub letters: Vec<Letter<'static>>,
+    pub speed: Vec<SpeedEstimate>,
+    /// pitch of the tone in Hz, when decoded from audio
+    pub frequency: Option<f32>,
+}
+
+/// classifier that follows the sender speed: dot and dash lengths are
+/// tracked separately as they are recognized and the latest gaps are
+/// clustered to tell letters and words apart, no fixed timing is needed
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveClassifier {
+    /// how many of the latest gaps are clustered
+    pub window: usize,
+    /// weight of the newest mark in the dot and dash averages
+    pub smoothing: f32,
+    /// relative change of speed needed to report a new estimate
+    pub report_change: f32,
+}
+
+impl Default for AdaptiveClassifier {
+    fn default() -> Self {
+        AdaptiveClassifier {
+            window: 16,
+            smoothing: 0.3,
+            report_change: 0.05,
+        }
+    }
+}
+
+impl AdaptiveClassifier {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// meaning of every run and the speed estimated over time
+    pub fn symbols(&self, runs: &[Run]) -> (Vec<Symbol>, Vec<SpeedEstimate>) {
+        let mut symbols: Vec<Symbol> = Vec::with_capacity(runs.len());
+        let mut speed: Vec<SpeedEstimate> = Vec::new();
+        // first guess from the beginning of the transmission
+        let Some(mut tracker) = SpeedTracker::from_runs(*self, runs) else {
+            return (symbols, speed);
+        };
+        let mut time = 0_f32;
+        for run in runs {
+            let dot = tracker.dot();
+            let text = tracker.push(run);
+            if run.key_down {
+                let current = wpm(tracker.dot());
+                let changed = speed
+                    .last()
+                    .is_none_or(|last| (current - last.wpm).abs() > last.wpm * self.report_change);
+                if changed {
+                    speed.push(SpeedEstimate { time, wpm: current });
+                }
+            }
+            symbols.push(Symbol {
+                run: *run,
+                time,
+                dot,
+                text,
+            });
+            time += run.duration;
+        }
+        (symbols, speed)
+    }
+
+    /// morse text of the runs and the speed estimated over time
+    pub fn classify(&self, runs: &[Run]) -> (String, Vec<SpeedEstimate>) {
+        let (symbols, speed) = self.symbols(runs);
+        (symbols.iter().map(|symbol| symbol.text).collect(), speed)
+    }
+
+    /// letters of the runs, `*` for the codes not in the alphabet
+    pub fn decode(&self, runs: &[Run]) -> Result<Transcript, String> {
+        let (morse, speed) = self.classify(runs);
+        let letters = morse
+            .split_whitespace()
+            .map(|code| Letter::from_str(code).unwrap_or(morse_alphabet::UNKNOWN))
+            .collect();
+        Ok(Transcript {
+            letters,
+            speed,
+            frequency: None,
+        })
+    }
+}
+
+// gaps at least this many dots long are pauses, always between words
+const PAUSE_UNITS: f32 = 14.0;
+
+/// state of an `AdaptiveClassifier` while it goes through the runs one at a time
+#[derive(Debug, Clone)]
+pub struct SpeedTracker {
+    settings: AdaptiveClassifier,
+    dot_len: f32,
+    dash_len: f32,
+    gaps: VecDeque<f32>,
+}
+
+impl SpeedTracker {
+    pub fn new(settings: AdaptiveClassifier, first_dot: f32) -> Self {
+        SpeedTracker {
+            settings,
+            dot_len: first_dot,
+            dash_len: first_dot * 3.0,
+            gaps: VecDeque::with_capacity(settings.window.max(2) + 1),
+        }
+    }
+
+    /// start from the dot length guessed on the first runs
+    pub fn from_runs(settings: AdaptiveClassifier, runs: &[Run]) -> Option<Self> {
+        let window = settings.window.max(2);
+        estimate_dot(&runs[..runs.len().min(window * 2)]).map(|dot| Self::new(settings, dot))
+    }
+
+    /// current length of a dot in seconds
+    pub fn dot(&self) -> f32 {
+        (self.dot_len + self.dash_len / 3.0) / 2.0
+    }
+
+    /// gaps at least this long end a letter
+    pub fn letter_gap(&self) -> f32 {
+        self.dot() * 2.0
+    }
+
+    // boundary between letter gaps and word gaps, senders often stretch them
+    fn word_gap(&self) -> f32 {
+        let dot = self.dot();
+        let long_gaps: Vec<f32> = self
+            .gaps
+            .iter()
+            .copied()
+            .filter(|g| *g > dot * 2.0)
+            .collect();
+        if long_gaps.is_empty() {
+            return dot * 5.0;
+        }
+        let (letter, word) = two_means(&long_gaps);
+        if word > letter * 1.6 {
+            (letter + word) / 2.0
+        } else {
+            dot * 5.0
+        }
+    }
+
+    /// classify the run and learn from it, same text as `Symbol::text`
+    pub fn push(&mut self, run: &Run) -> &'static str {
+        let smoothing = self.settings.smoothing;
+        if run.key_down {
+            if run.duration < (self.dot_len + self.dash_len) / 2.0 {
+                self.dot_len += (run.duration - self.dot_len) * smoothing;
+                "."
+            } else {
+                self.dash_len += (run.duration - self.dash_len) * smoothing;
+                "-"
+            }
+        } else {
+            let dot = self.dot();
+            // pauses between paragraphs would hide the word gaps from the clustering
+            if run.duration >= dot * PAUSE_UNITS {
+                return " / ";
+            }
+            self.gaps.push_back(run.duration);
+            if self.gaps.len() > self.settings.window.max(2) {
+                self.gaps.pop_front();
+            }
+            if run.duration >= self.word_gap() {
+                " / "
+            } else if run.duration >= dot * 2.0 {
+                " "
+            } else {
+                ""
+            }
+        }
+    }
+}
+
+/// follow the speed of the sender and decode the runs
+pub fn decode_runs(runs: &[Run]) -> Result<Transcript, String> {
+    AdaptiveClassifier::new().decode(runs)
+}
+
+#[test]
+fn test_decode_runs() {
+    let states = "   #_###___#_#_______#  ".chars().map(|ch| ch == '#');
+    let runs = runs_from_states(states, 0.01);
+    assert_eq!(runs.len(), 9);
+    let text = Letter::concat_text(decode_runs(&runs).unwrap().letters);
+    assert_eq!(String::from_utf8(text).unwrap(), "ai e");
+
+    // a code out of the alphabet doesn't fail the rest
+    let states = "#_#_#_#_#_#_#___#__".chars().map(|ch| ch == '#');
+    let text = Letter::concat_text(
+        decode_runs(&runs_from_states(states, 0.01))
+            .unwrap()
+            .letters,
+    );
+    assert_eq!(String::from_utf8(text).unwrap(), "*e");
+}
+
+#[test]
+fn test_speed_change() {
+    use crate::keying::{keying, Key};
+
+    let letters = "paris paris paris"
+        .chars()
+        .map(|ch| Letter::from_str(&ch.to_string()).unwrap());
+    let keys = keying(letters);
+    // the sender speeds up from 12 to 24 wpm
+    let len = keys.len() as f32;
+    let runs: Vec<Run> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let dot = 0.1 - 0.05 * i as f32 / len;
+            match key {
+                Key::Down(units) => Run {
+                    key_down: true,
+                    duration: dot * *units as f32,
+                },
+                Key::Up(units) => Run {
+                    key_down: false,
+                    duration: dot * *units as f32,
+                },
+            }
+        })
+        .collect();
+    let transcript = decode_runs(&runs).unwrap();
+    assert_eq!(
+        String::from_utf8(Letter::concat_text(transcript.letters)).unwrap(),
+        "paris paris paris"
+    );
+    let first = transcript.speed.first().unwrap();
+    let last = transcript.speed.last().unwrap();
+    assert!(first.wpm < 14.0, "{:?}", first);
+    assert!(last.wpm > 18.0, "{:?}", last);
+}
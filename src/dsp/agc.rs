@@ -0,0 +1,56 @@
+use super::envelope::EnvelopeFollower;
+use super::Stage;
+
+/// automatic gain control, brings the peak level of the signal to `target`
+#[derive(Debug, Clone, Copy)]
+pub struct Agc {
+    target: f32,
+    max_gain: f32,
+    detector: EnvelopeFollower,
+}
+
+impl Agc {
+    /// instant attack, so the output never overshoots the target,
+    /// and slow release, fit for keyed signals
+    pub fn new(sample_rate: f32, target: f32) -> Self {
+        Self::with_times(sample_rate, target, 0.0, 0.5)
+    }
+
+    pub fn with_times(sample_rate: f32, target: f32, attack: f32, release: f32) -> Self {
+        Agc {
+            target,
+            max_gain: 100.0,
+            detector: EnvelopeFollower::new(sample_rate, attack, release),
+        }
+    }
+
+    /// limit how much silence and noise can be amplified
+    pub fn max_gain(&mut self, max_gain: f32) -> &mut Self {
+        self.max_gain = max_gain;
+        self
+    }
+}
+
+impl Stage for Agc {
+    fn process(&mut self, sample: f32) -> f32 {
+        let peak = self.detector.process(sample);
+        let gain = (self.target / peak.max(f32::EPSILON)).min(self.max_gain);
+        sample * gain
+    }
+}
+
+#[test]
+fn test_agc() {
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    let sample_rate = SAMPLE_RATE as f32;
+    for volume in [0.02, 0.2, 0.9] {
+        let mut agc = Agc::new(sample_rate, 0.5);
+        let mut wave = Note(600.0).float_wave(1.0, &Amplitude::Custom(volume));
+        agc.process_all(&mut wave);
+        let peak = wave[wave.len() / 2..]
+            .iter()
+            .fold(0_f32, |a, b| a.max(b.abs()));
+        assert!((peak - 0.5).abs() < 0.1, "{} {}", volume, peak);
+    }
+}
@@ -0,0 +1,122 @@
+use super::Stage;
+use std::f32::consts::PI;
+
+/// second order IIR filter, coefficients from the RBJ audio EQ cookbook
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    // last two inputs and outputs
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    // normalize the coefficients by a0
+    fn new(b: [f32; 3], a: [f32; 3]) -> Self {
+        Biquad {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    /// pass frequencies below `cutoff` Hz
+    pub fn low_pass(sample_rate: f32, cutoff: f32, q: f32) -> Self {
+        let w = 2.0 * PI * cutoff / sample_rate;
+        let alpha = f32::sin(w) / (2.0 * q);
+        let cos = f32::cos(w);
+        Self::new(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// pass frequencies above `cutoff` Hz
+    pub fn high_pass(sample_rate: f32, cutoff: f32, q: f32) -> Self {
+        let w = 2.0 * PI * cutoff / sample_rate;
+        let alpha = f32::sin(w) / (2.0 * q);
+        let cos = f32::cos(w);
+        Self::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// pass frequencies around `center` Hz with unity gain at the center,
+    /// the bandwidth is `center / q`
+    pub fn band_pass(sample_rate: f32, center: f32, q: f32) -> Self {
+        let w = 2.0 * PI * center / sample_rate;
+        let alpha = f32::sin(w) / (2.0 * q);
+        let cos = f32::cos(w);
+        Self::new([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// gain of the filter at `frequency` Hz
+    pub fn response(&self, sample_rate: f32, frequency: f32) -> f32 {
+        let w = 2.0 * PI * frequency / sample_rate;
+        // evaluate b(z) / a(z) with z = e^(jw)
+        let eval = |c0: f32, c1: f32, c2: f32| {
+            let re = c0 + c1 * f32::cos(w) + c2 * f32::cos(2.0 * w);
+            let im = -c1 * f32::sin(w) - c2 * f32::sin(2.0 * w);
+            (re * re + im * im).sqrt()
+        };
+        eval(self.b0, self.b1, self.b2) / eval(1.0, self.a1, self.a2)
+    }
+}
+
+impl Stage for Biquad {
+    fn process(&mut self, sample: f32) -> f32 {
+        let output = self.b0 * sample + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = sample;
+        self.y2 = self.y1;
+        self.y1 = output;
+        output
+    }
+}
+
+#[test]
+fn test_frequency_response() {
+    use super::rms;
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    let sample_rate = SAMPLE_RATE as f32;
+    // measured gain of a steady sine after the filter settled
+    let measure = |mut filter: Biquad, frequency: f32| {
+        let mut wave = Note(frequency).float_wave(0.5, &Amplitude::Custom(1.0));
+        filter.process_all(&mut wave);
+        rms(&wave[wave.len() / 2..]) * std::f32::consts::SQRT_2
+    };
+
+    let band_pass = Biquad::band_pass(sample_rate, 700.0, 7.0);
+    assert!((band_pass.response(sample_rate, 700.0) - 1.0).abs() < 0.01);
+    assert!((measure(band_pass, 700.0) - 1.0).abs() < 0.02);
+    // -3 dB at center +- bandwidth / 2
+    let edge = 700.0 + 50.0;
+    assert!((band_pass.response(sample_rate, edge) - 0.707).abs() < 0.05);
+    assert!(measure(band_pass, 1400.0) < 0.1);
+    assert!(measure(band_pass, 350.0) < 0.1);
+
+    let low_pass = Biquad::low_pass(sample_rate, 1000.0, std::f32::consts::FRAC_1_SQRT_2);
+    assert!((measure(low_pass, 200.0) - 1.0).abs() < 0.02);
+    assert!((low_pass.response(sample_rate, 1000.0) - 0.707).abs() < 0.01);
+    assert!(measure(low_pass, 8000.0) < 0.02);
+
+    let high_pass = Biquad::high_pass(sample_rate, 300.0, std::f32::consts::FRAC_1_SQRT_2);
+    assert!(measure(high_pass, 50.0) < 0.05);
+    assert!((measure(high_pass, 3000.0) - 1.0).abs() < 0.02);
+}
@@ -0,0 +1,63 @@
+use super::Stage;
+
+// smoothing coefficient reaching ~63% of a step in `seconds`
+pub(crate) fn time_constant(sample_rate: f32, seconds: f32) -> f32 {
+    if seconds <= 0.0 {
+        return 1.0;
+    }
+    1.0 - f32::exp(-1.0 / (seconds * sample_rate))
+}
+
+/// follows the peak amplitude of a signal, rising with the attack time
+/// and falling with the release time
+#[derive(Debug, Clone, Copy)]
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    level: f32,
+}
+
+impl EnvelopeFollower {
+    pub fn new(sample_rate: f32, attack: f32, release: f32) -> Self {
+        EnvelopeFollower {
+            attack: time_constant(sample_rate, attack),
+            release: time_constant(sample_rate, release),
+            level: 0.0,
+        }
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
+
+impl Stage for EnvelopeFollower {
+    fn process(&mut self, sample: f32) -> f32 {
+        let input = sample.abs();
+        let coeff = if input > self.level {
+            self.attack
+        } else {
+            self.release
+        };
+        self.level += (input - self.level) * coeff;
+        self.level
+    }
+}
+
+#[test]
+fn test_envelope() {
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    let sample_rate = SAMPLE_RATE as f32;
+    let mut follower = EnvelopeFollower::new(sample_rate, 0.0005, 0.05);
+    let mut wave = Note(700.0).float_wave(0.2, &Amplitude::Custom(0.8));
+    let tone_end = wave.len();
+    wave.extend(vec![0.0; SAMPLE_RATE as usize * 3 / 10]);
+    follower.process_all(&mut wave);
+    assert!(
+        (wave[tone_end - 1] - 0.8).abs() < 0.05,
+        "{}",
+        wave[tone_end - 1]
+    );
+    assert!(wave[wave.len() - 1] < 0.01);
+}
@@ -0,0 +1,72 @@
+//public modules
+pub mod agc;
+pub mod biquad;
+pub mod envelope;
+
+// public uses
+pub use agc::Agc;
+pub use biquad::Biquad;
+pub use envelope::EnvelopeFollower;
+
+/// processing step applied sample by sample, stages keep their own state
+pub trait Stage {
+    fn process(&mut self, sample: f32) -> f32;
+
+    /// process a whole buffer in place
+    fn process_all(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
+/// stages applied one after the other
+#[derive(Default)]
+pub struct Chain {
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push<S: Stage + 'static>(&mut self, stage: S) -> &mut Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+impl Stage for Chain {
+    fn process(&mut self, sample: f32) -> f32 {
+        self.stages
+            .iter_mut()
+            .fold(sample, |sample, stage| stage.process(sample))
+    }
+}
+
+// root mean square, used to measure the responses in the tests
+#[cfg(test)]
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[test]
+fn test_chain() {
+    use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+
+    let sample_rate = SAMPLE_RATE as f32;
+    let mut chain = Chain::new();
+    chain
+        .push(Biquad::band_pass(sample_rate, 700.0, 5.0))
+        .push(Agc::new(sample_rate, 0.5));
+    let mut wave = Note(700.0).float_wave(2.0, &Amplitude::Custom(0.05));
+    chain.process_all(&mut wave);
+    // the quiet tone passes the filter and is brought up to the target
+    let level = rms(&wave[wave.len() / 2..]) * std::f32::consts::SQRT_2;
+    assert!((level - 0.5).abs() < 0.05, "{}", level);
+}
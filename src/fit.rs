@@ -0,0 +1,121 @@
+use crate::keying::{Key, LETTER_GAP_UNITS};
+use crate::markup::Voice;
+use std::str::FromStr;
+
+// units in the standard word PARIS, gaps between its letters and after it included
+const PARIS_UNITS: u32 = 50;
+
+/// what changes to send a message in a given time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitMode {
+    /// the speed of the whole message
+    Wpm,
+    /// only the gaps between letters and words, the letters keep their speed
+    Farnsworth,
+}
+
+impl FromStr for FitMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wpm" => Ok(FitMode::Wpm),
+            "farnsworth" => Ok(FitMode::Farnsworth),
+            _ => Err(format!("Fit mode not found: {}", s)),
+        }
+    }
+}
+
+/// length of a keyed message in dot units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Length {
+    /// units of the letters, their symbols and the gaps inside them
+    pub letters: u32,
+    /// units of the gaps between letters and words, stretched by farnsworth timing
+    pub gaps: u32,
+}
+
+impl Length {
+    pub fn of(keys: &[Key]) -> Self {
+        let mut length = Length::default();
+        for key in keys {
+            match *key {
+                Key::Up(units) if units >= LETTER_GAP_UNITS => length.gaps += units,
+                Key::Down(units) | Key::Up(units) => length.letters += units,
+            }
+        }
+        length
+    }
+
+    pub fn units(&self) -> u32 {
+        self.letters + self.gaps
+    }
+
+    /// the length in standard PARIS words of 50 units
+    pub fn paris_words(&self) -> f32 {
+        self.units() as f32 / PARIS_UNITS as f32
+    }
+
+    /// seconds taken to send the message with `voice`
+    pub fn duration(&self, voice: &Voice) -> f32 {
+        self.letters as f32 * voice.dot() + self.gaps as f32 * voice.gap_unit()
+    }
+
+    /// `voice` changed so that the message lasts `seconds`, with farnsworth
+    /// spacing the letters are sent at the speed of the voice and the gaps
+    /// can only be longer than standard
+    pub fn fit(&self, seconds: f32, mut voice: Voice, mode: FitMode) -> Result<Voice, String> {
+        if seconds <= 0.0 || self.units() == 0 {
+            return Err(format!("Nothing to send in {} seconds", seconds));
+        }
+        match mode {
+            FitMode::Wpm => {
+                voice.wpm = 1.2 * self.units() as f32 / seconds;
+                voice.farnsworth = None;
+            }
+            FitMode::Farnsworth => {
+                let standard = self.units() as f32 * voice.dot();
+                if self.gaps == 0 || seconds < standard {
+                    return Err(format!(
+                        "The message takes {:.1} seconds at {} wpm, it can't be sent in {} seconds with farnsworth spacing",
+                        standard, voice.wpm, seconds
+                    ));
+                }
+                let gap_unit = (seconds - self.letters as f32 * voice.dot()) / self.gaps as f32;
+                // the ARRL formula of `Voice::gap_unit` solved for the overall speed
+                voice.farnsworth = Some(60.0 * voice.wpm / (19.0 * gap_unit * voice.wpm + 37.2));
+            }
+        }
+        Ok(voice)
+    }
+}
+
+#[test]
+fn test_fit() {
+    use crate::keying::keying;
+    use crate::Letter;
+
+    let paris = "paris ".chars().map(|ch| Letter::from_char(ch).unwrap());
+    let mut keys = keying(paris);
+    // the word gap at the end is sent before the next message
+    keys.push(Key::Up(7));
+    let length = Length::of(&keys);
+    assert_eq!(length.units(), PARIS_UNITS);
+    assert_eq!(length.paris_words(), 1.0);
+    assert_eq!(length.gaps, 19);
+
+    let voice = length.fit(3.0, Voice::default(), FitMode::Wpm).unwrap();
+    assert!((voice.wpm - 20.0).abs() < 1e-3);
+    assert!((length.duration(&voice) - 3.0).abs() < 1e-3);
+
+    let fast = Voice {
+        wpm: 20.0,
+        ..Default::default()
+    };
+    let voice = length.fit(12.0, fast, FitMode::Farnsworth).unwrap();
+    assert_eq!(voice.wpm, 20.0);
+    assert!((voice.farnsworth.unwrap() - 5.0).abs() < 1e-3);
+    assert!((length.duration(&voice) - 12.0).abs() < 1e-3);
+    // farnsworth spacing can't make the message faster
+    assert!(length.fit(2.0, fast, FitMode::Farnsworth).is_err());
+    assert_eq!("Farnsworth".parse::<FitMode>(), Ok(FitMode::Farnsworth));
+}
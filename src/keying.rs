@@ -0,0 +1,192 @@
+use crate::polyphonia::{Amplitude, Note, SAMPLE_RATE};
+use crate::{morse_alphabet, Letter};
+use std::str::FromStr;
+
+/// state of the key held for a number of time units (the length of a dot)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Down(u32),
+    Up(u32),
+}
+
+pub const DOT_UNITS: u32 = 1;
+pub const LINE_UNITS: u32 = 3;
+// silence between the symbols of a letter, between letters and between words
+pub const SYMBOL_GAP_UNITS: u32 = 1;
+pub const LETTER_GAP_UNITS: u32 = 3;
+pub const WORD_GAP_UNITS: u32 = 7;
+
+/// sequence of key presses and releases needed to send `letters`
+/// following the standard 1:3:7 timing
+pub fn keying<'a, T: Iterator<Item = Letter<'a>>>(letters: T) -> Vec<Key> {
+    let mut output: Vec<Key> = Vec::new();
+    // silence to add before the next symbol, none before the first one
+    let mut pending_gap = 0;
+    for letter in letters {
+        if letter == morse_alphabet::SPACE {
+            pending_gap = WORD_GAP_UNITS;
+            continue;
+        }
+        let Letter(_, morse) = letter;
+        for symbol in morse.chars() {
+            let units = match symbol {
+                '.' => DOT_UNITS,
+                '-' => LINE_UNITS,
+                _ => continue,
+            };
+            if pending_gap > 0 {
+                output.push(Key::Up(pending_gap));
+            }
+            output.push(Key::Down(units));
+            pending_gap = SYMBOL_GAP_UNITS;
+        }
+        pending_gap = pending_gap.max(LETTER_GAP_UNITS);
+    }
+    output
+}
+
+/// what is sent at the end of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBreak {
+    /// only a word gap
+    Space,
+    /// the `<bt>` prosign, `-...-`
+    Bt,
+    /// the `<aa>` prosign, `.-.-`
+    Aa,
+}
+
+impl FromStr for LineBreak {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "space" => Ok(LineBreak::Space),
+            "bt" => Ok(LineBreak::Bt),
+            "aa" => Ok(LineBreak::Aa),
+            _ => Err(format!("Line break not found: {}", s)),
+        }
+    }
+}
+
+impl LineBreak {
+    fn prosign(&self) -> Option<Letter<'static>> {
+        match self {
+            LineBreak::Space => None,
+            LineBreak::Bt => Some(morse_alphabet::EQUALS),
+            LineBreak::Aa => Some(morse_alphabet::NEW_LINE),
+        }
+    }
+}
+
+/// keys between the last letter of a line and the first of the next one,
+/// the silence lasts at least `paragraph_gap` units when a paragraph ends
+pub fn line_gap(line_break: LineBreak, paragraph_gap: Option<u32>) -> Vec<Key> {
+    let mut output: Vec<Key> = Vec::new();
+    if let Some(prosign) = line_break.prosign() {
+        output.push(Key::Up(WORD_GAP_UNITS));
+        output.extend(keying([prosign].into_iter()));
+    }
+    output.push(Key::Up(paragraph_gap.unwrap_or(0).max(WORD_GAP_UNITS)));
+    output
+}
+
+/// keying of the paragraphs of several lines, separated by blank lines,
+/// the lines of a paragraph are ended by `line_break`
+pub fn keying_paragraphs<'a>(lines: Vec<Vec<Letter<'a>>>, line_break: LineBreak) -> Vec<Vec<Key>> {
+    let mut output: Vec<Vec<Key>> = Vec::new();
+    let mut paragraph = true;
+    for line in lines {
+        if line.iter().all(|letter| *letter == morse_alphabet::SPACE) {
+            paragraph = true;
+            continue;
+        }
+        match output.last_mut() {
+            Some(keys) if !paragraph => {
+                keys.extend(line_gap(line_break, None));
+                keys.extend(keying(line.into_iter()));
+            }
+            _ => output.push(keying(line.into_iter())),
+        }
+        paragraph = false;
+    }
+    output
+}
+
+/// keying of several lines, each ended by `line_break`, with at least
+/// `paragraph_gap` units of silence where blank lines separate paragraphs
+pub fn keying_lines<'a>(
+    lines: Vec<Vec<Letter<'a>>>,
+    line_break: LineBreak,
+    paragraph_gap: u32,
+) -> Vec<Key> {
+    keying_paragraphs(lines, line_break).join(&line_gap(line_break, Some(paragraph_gap))[..])
+}
+
+/// float samples of `keys` played with `note`, every unit lasts `dot` seconds
+pub fn render(keys: &[Key], note: &Note, dot: f32, volume: &Amplitude) -> Vec<f32> {
+    let mut output: Vec<f32> = Vec::new();
+    for key in keys {
+        match key {
+            Key::Down(units) => output.extend(note.float_wave(dot * *units as f32, volume)),
+            Key::Up(units) => output.resize(
+                output.len() + (dot * *units as f32 * SAMPLE_RATE as f32) as usize,
+                0.0,
+            ),
+        }
+    }
+    output
+}
+
+#[test]
+fn test_keying() {
+    let letters = ["a", " ", "e", "t"].map(|x| Letter::from_str(x).unwrap());
+    assert_eq!(
+        keying(letters.into_iter()),
+        vec![
+            Key::Down(1),
+            Key::Up(1),
+            Key::Down(3),
+            Key::Up(7),
+            Key::Down(1),
+            Key::Up(3),
+            Key::Down(3),
+        ]
+    );
+
+    let lines = |lines: &[&str]| -> Vec<Vec<Letter>> {
+        lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .map(|ch| Letter::from_char(ch).unwrap())
+                    .collect()
+            })
+            .collect()
+    };
+    assert_eq!(
+        keying_lines(lines(&["e", "", "", "t e"]), LineBreak::Space, 20),
+        vec![
+            Key::Down(1),
+            Key::Up(20),
+            Key::Down(3),
+            Key::Up(7),
+            Key::Down(1)
+        ]
+    );
+    assert_eq!(
+        keying_lines(lines(&["e", "e"]), LineBreak::Aa, 20),
+        vec![
+            Key::Down(1),
+            Key::Up(7),
+            Key::Down(1),
+            Key::Up(1),
+            Key::Down(3),
+            Key::Up(1),
+            Key::Down(1),
+            Key::Up(1),
+            Key::Down(3),
+            Key::Up(7),
+            Key::Down(1),
+        ]
+    );
+}
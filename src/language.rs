@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+
+// log probability of a character no word can explain
+const UNKNOWN_CHAR: f32 = -4.0;
+// log probability paid for every word, favours fewer and longer words
+const WORD_COST: f32 = -1.0;
+
+// count given to the character sequences never seen while training
+const SMOOTHING: f32 = 0.1;
+
+// text used to train the default character model: plain english and ham radio traffic
+const CORPUS: &str = "the quick brown fox jumps over the lazy dog \
+    hello world this is a test of the morse code translator \
+    we are going to send a message and then listen for the answer \
+    there is no time like the present and all is well that ends well \
+    what is your name where are you from how is the weather today \
+    thanks for the call your signal is good here the weather is fine \
+    cq cq cq de k1abc k1abc k qrz de w1aw name is john qth boston \
+    rst 599 5nn tnx fer call hw cpy om fb 73 es gl sk \
+    my rig is running 100 watts into a dipole antenna up 30 feet \
+    please repeat your call sign i did not copy all of it \
+    paris paris sos sos help need assistance at once \
+    one two three four five six seven eight nine ten";
+
+/// scores how likely a text is, as a log probability built one character at a time
+pub trait LanguageModel {
+    /// log probability of `next` following `text`
+    fn next_score(&self, text: &str, next: char) -> f32;
+
+    /// log probability of the text ending here
+    fn end_score(&self, _text: &str) -> f32 {
+        0.0
+    }
+
+    /// log probability of the whole text
+    fn score(&self, text: &str) -> f32 {
+        let mut score = 0.0;
+        for (i, ch) in text.char_indices() {
+            score += self.next_score(&text[..i], ch);
+        }
+        score + self.end_score(text)
+    }
+}
+
+/// dictionary of known words, a text is likely when it splits into them
+#[derive(Debug, Clone, Default)]
+pub struct WordList {
+    words: HashSet<String>,
+    // every beginning of a word, to score words still being spelled
+    prefixes: HashSet<String>,
+    longest: usize,
+}
+
+impl WordList {
+    pub fn new<'a, I: IntoIterator<Item = &'a str>>(words: I) -> Self {
+        let mut list = WordList::default();
+        for word in words {
+            list.insert(word);
+        }
+        list
+    }
+
+    /// one word per line, empty lines and lines starting with `#` are skipped
+    pub fn parse(text: &str) -> Self {
+        Self::new(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        )
+    }
+
+    pub fn insert(&mut self, word: &str) -> &mut Self {
+        let word = word.to_lowercase();
+        for (i, _) in word.char_indices().skip(1) {
+            self.prefixes.insert(word[..i].to_string());
+        }
+        self.longest = self.longest.max(word.chars().count());
+        self.words.insert(word);
+        self
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(String::as_str)
+    }
+
+    // best split of `text` into words, spaces and unknown characters,
+    // with `open` the last word may be incomplete
+    fn split_score(&self, text: &str, open: bool) -> f32 {
+        let chars: Vec<char> = text.to_lowercase().chars().collect();
+        let mut best = vec![f32::NEG_INFINITY; chars.len() + 1];
+        best[0] = 0.0;
+        for start in 0..chars.len() {
+            if best[start] == f32::NEG_INFINITY {
+                continue;
+            }
+            let skip = if chars[start] == ' ' {
+                0.0
+            } else {
+                UNKNOWN_CHAR
+            };
+            best[start + 1] = best[start + 1].max(best[start] + skip);
+            for end in start + 1..=chars.len().min(start + self.longest) {
+                let word: String = chars[start..end].iter().collect();
+                let known = self.words.contains(&word)
+                    || (open && end == chars.len() && self.prefixes.contains(&word));
+                if known {
+                    best[end] = best[end].max(best[start] + WORD_COST);
+                }
+            }
+        }
+        best[chars.len()]
+    }
+}
+
+impl LanguageModel for WordList {
+    fn next_score(&self, text: &str, next: char) -> f32 {
+        let mut extended = text.to_string();
+        extended.push(next);
+        self.split_score(&extended, true) - self.split_score(text, true)
+    }
+
+    fn end_score(&self, text: &str) -> f32 {
+        self.split_score(text, false) - self.split_score(text, true)
+    }
+}
+
+/// character n-gram model with additive smoothing
+#[derive(Debug, Clone)]
+pub struct NgramModel {
+    order: usize,
+    // how many times every sequence of up to `order` characters was seen
+    counts: HashMap<String, u32>,
+    alphabet: usize,
+}
+
+impl NgramModel {
+    /// learn from `corpus` how often every character follows the `order - 1` before it
+    pub fn train(corpus: &str, order: usize) -> Self {
+        let order = order.max(1);
+        let corpus = corpus.to_lowercase();
+        // the text starts like a word after a space
+        let chars: Vec<char> = std::iter::repeat_n(' ', order - 1)
+            .chain(corpus.chars())
+            .collect();
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for end in order - 1..chars.len() {
+            let context: String = chars[end + 1 - order..end].iter().collect();
+            *counts.entry(context.clone()).or_default() += 1;
+            let mut gram = context;
+            gram.push(chars[end]);
+            *counts.entry(gram).or_default() += 1;
+        }
+        let alphabet = corpus.chars().collect::<HashSet<char>>().len();
+        NgramModel {
+            order,
+            counts,
+            alphabet,
+        }
+    }
+
+    /// trigrams of english text and common ham radio traffic
+    pub fn english() -> Self {
+        Self::train(CORPUS, 3)
+    }
+}
+
+impl Default for NgramModel {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl LanguageModel for NgramModel {
+    fn next_score(&self, text: &str, next: char) -> f32 {
+        let history: Vec<char> = std::iter::repeat_n(' ', self.order - 1)
+            .chain(text.to_lowercase().chars())
+            .collect();
+        let context: String = history[history.len() + 1 - self.order..].iter().collect();
+        let mut gram = context.clone();
+        gram.extend(next.to_lowercase());
+        let seen = |key: &str| self.counts.get(key).copied().unwrap_or(0) as f32;
+        ((seen(&gram) + SMOOTHING) / (seen(&context) + SMOOTHING * (self.alphabet as f32 + 1.0)))
+            .ln()
+    }
+
+    // the text must end where a word can end
+    fn end_score(&self, text: &str) -> f32 {
+        self.next_score(text, ' ')
+    }
+}
+
+#[test]
+fn test_models() {
+    let words = WordList::new(["hello", "world"]);
+    assert!(words.score("hello world") > words.score("hellp world"));
+    assert!(words.score("helloworld") > words.score("hleloworld"));
+    // an incomplete word costs nothing until the end
+    assert_eq!(words.next_score("hel", 'l'), 0.0);
+
+    let ngrams = NgramModel::english();
+    assert!(ngrams.score("the") > ngrams.score("tqe"));
+    assert!(ngrams.score("hello") > ngrams.score("hrllo"));
+}
@@ -1,20 +1,51 @@
+pub mod abbreviations;
+pub mod beacon;
+pub mod correction;
+pub mod cut_numbers;
+pub mod decoder;
+pub mod dsp;
+pub mod fit;
+pub mod json;
+pub mod keying;
+pub mod language;
+pub mod markup;
+pub mod normalizer;
+pub mod notation;
 pub mod parser;
 pub mod polyphonia;
+pub mod prosigns;
+pub mod segmenter;
+pub mod separators;
 pub mod translator;
+pub mod transliteration;
 pub mod utils;
 pub mod wav;
 
+use keying::{keying, Key};
 use polyphonia::{notable_notes, Amplitude};
 use std::str::FromStr;
 
-const DOT_DURATION: f32 = 0.1;
-const LINE_DURATION: f32 = DOT_DURATION * 2.0;
-const SLASH_DURATION: f32 = DOT_DURATION * 4.0;
+pub const DOT_DURATION: f32 = 0.1;
 
 #[derive(Debug)]
 pub struct Letter<'a>(&'a str, &'a str);
 
 impl<'a> Letter<'a> {
+    /// letter sent for a character of text, unlike `from_str` a `.`, `-` or `/`
+    /// is read as punctuation and never as morse
+    pub fn from_char(ch: char) -> Result<Letter<'static>, String> {
+        if ch == ' ' {
+            return Ok(morse_alphabet::SPACE);
+        }
+        let lower: String = ch.to_lowercase().collect();
+        morse_alphabet::LETTERS
+            .iter()
+            .chain(morse_alphabet::PUNCTUATION.iter())
+            .find(|Letter(text, _)| *text == lower)
+            .map(|Letter(text, morse)| Letter(text, morse))
+            .ok_or_else(|| format!("No representation found for the character: {}", ch))
+    }
+
     pub fn concat_morse(args: Vec<Letter<'_>>) -> Vec<u8> {
         let mut iter_args = args.iter();
         let first_letter = iter_args.next();
@@ -46,23 +77,21 @@ impl<'a> Letter<'a> {
     }
 
     pub fn concat_audio<T: Iterator<Item = Letter<'a>>>(args: T) -> Vec<i16> {
+        Self::keys_audio(&keying(args))
+    }
+
+    /// audio of the key presses and releases, with the default tone and speed
+    pub fn keys_audio(keys: &[Key]) -> Vec<i16> {
         let mut output: Vec<i16> = Vec::new();
-        for ch in args
-            .map(|x| -> &str {
-                let Self(_, y) = x;
-                y
-            })
-            .flat_map(|x| x.chars())
-        {
-            let chunk = match ch {
-                '.' => notable_notes::A4.audio_wave(DOT_DURATION, &Amplitude::Medium),
-                '-' => notable_notes::A4.audio_wave(LINE_DURATION, &Amplitude::Medium),
-                '/' => notable_notes::A4.audio_wave(SLASH_DURATION, &Amplitude::Silent),
-                _ => Vec::new(),
-            };
+        for key in keys {
+            let chunk =
+                match key {
+                    Key::Down(units) => notable_notes::A4
+                        .audio_wave(DOT_DURATION * *units as f32, &Amplitude::Medium),
+                    Key::Up(units) => notable_notes::A4
+                        .audio_wave(DOT_DURATION * *units as f32, &Amplitude::Silent),
+                };
             output.extend_from_slice(&chunk);
-            output
-                .extend_from_slice(&notable_notes::G0.audio_wave(DOT_DURATION, &Amplitude::Silent))
         }
         output
     }
@@ -108,6 +137,79 @@ pub mod morse_alphabet {
     pub const NINE: Letter = Letter("9", "----.");
     pub const ZERO: Letter = Letter("0", "-----");
     pub const SPACE: Letter = Letter(" ", "/");
+    pub const PERIOD: Letter = Letter(".", ".-.-.-");
+    pub const COMMA: Letter = Letter(",", "--..--");
+    pub const COLON: Letter = Letter(":", "---...");
+    pub const QUESTION_MARK: Letter = Letter("?", "..--..");
+    pub const APOSTROPHE: Letter = Letter("'", ".----.");
+    pub const HYPHEN: Letter = Letter("-", "-....-");
+    pub const SLASH: Letter = Letter("/", "-..-.");
+    pub const LEFT_PARENTHESIS: Letter = Letter("(", "-.--.");
+    pub const RIGHT_PARENTHESIS: Letter = Letter(")", "-.--.-");
+    pub const QUOTATION_MARK: Letter = Letter("\"", ".-..-.");
+    pub const EQUALS: Letter = Letter("=", "-...-");
+    pub const PLUS: Letter = Letter("+", ".-.-.");
+    pub const AT: Letter = Letter("@", ".--.-.");
+    /// sent after a mistake, the word is then sent again
+    pub const ERROR: Letter = Letter("<hh>", "........");
+    /// new line, the `<bt>` prosign is sent as `=`
+    pub const NEW_LINE: Letter = Letter("<aa>", ".-.-");
+    /// written by the decoders in place of a code not in the alphabet
+    pub const UNKNOWN: Letter = Letter("*", "");
+
+    /// every letter and digit, in alphabetical order
+    pub const LETTERS: [Letter<'static>; 36] = [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, ONE, TWO,
+        THREE, FOUR, FIVE, SIX, SEVEN, EIGHT, NINE, ZERO,
+    ];
+
+    /// punctuation marks of the ITU recommendation
+    pub const PUNCTUATION: [Letter<'static>; 13] = [
+        PERIOD,
+        COMMA,
+        COLON,
+        QUESTION_MARK,
+        APOSTROPHE,
+        HYPHEN,
+        SLASH,
+        LEFT_PARENTHESIS,
+        RIGHT_PARENTHESIS,
+        QUOTATION_MARK,
+        EQUALS,
+        PLUS,
+        AT,
+    ];
+
+    // prosigns with a letter of their own
+    const PROSIGNS: [Letter<'static>; 2] = [ERROR, NEW_LINE];
+
+    const fn longest(letters: &[Letter]) -> usize {
+        let mut longest = 0;
+        let mut i = 0;
+        while i < letters.len() {
+            if letters[i].1.len() > longest {
+                longest = letters[i].1.len();
+            }
+            i += 1;
+        }
+        longest
+    }
+
+    /// dots and dashes in the longest code of the alphabet, the shortest error prosign included
+    pub const LONGEST_CODE: usize = {
+        let (letters, punctuation, prosigns) =
+            (longest(&LETTERS), longest(&PUNCTUATION), longest(&PROSIGNS));
+        let longest = if letters > punctuation {
+            letters
+        } else {
+            punctuation
+        };
+        if prosigns > longest {
+            prosigns
+        } else {
+            longest
+        }
+    };
 }
 
 impl FromStr for Letter<'_> {
@@ -151,6 +253,22 @@ impl FromStr for Letter<'_> {
             "9" | "----." => Ok(morse_alphabet::NINE),
             "0" | "-----" => Ok(morse_alphabet::ZERO),
             " " | "/" => Ok(morse_alphabet::SPACE),
+            // the text of `.`, `-` and `/` is morse here, see `Letter::from_char`
+            ".-.-.-" => Ok(morse_alphabet::PERIOD),
+            "," | "--..--" => Ok(morse_alphabet::COMMA),
+            ":" | "---..." => Ok(morse_alphabet::COLON),
+            "?" | "..--.." => Ok(morse_alphabet::QUESTION_MARK),
+            "'" | ".----." => Ok(morse_alphabet::APOSTROPHE),
+            "-....-" => Ok(morse_alphabet::HYPHEN),
+            "-..-." => Ok(morse_alphabet::SLASH),
+            "(" | "-.--." => Ok(morse_alphabet::LEFT_PARENTHESIS),
+            ")" | "-.--.-" => Ok(morse_alphabet::RIGHT_PARENTHESIS),
+            "\"" | ".-..-." => Ok(morse_alphabet::QUOTATION_MARK),
+            "=" | "-...-" => Ok(morse_alphabet::EQUALS),
+            "+" | ".-.-." => Ok(morse_alphabet::PLUS),
+            "@" | ".--.-." => Ok(morse_alphabet::AT),
+            ".-.-" => Ok(morse_alphabet::NEW_LINE),
+            code if prosigns::is_error(code) => Ok(morse_alphabet::ERROR),
             _ => Err(format!("No representation found for the string: {}", s)),
         }
     }
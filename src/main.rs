@@ -1,23 +1,102 @@
 use clap::Parser;
-use morse_traducer::parser::MorseArgs;
+use morse_traducer::abbreviations::{AbbreviationStyle, Dictionary};
+use morse_traducer::beacon::Beacon;
+use morse_traducer::language::WordList;
+use morse_traducer::normalizer::Normalizer;
+use morse_traducer::parser::{InputFormat, MorseArgs, MorseCommand, MorseTraductionType};
+use morse_traducer::separators::Separators;
 use morse_traducer::translator::{MorseTranslator, TranslatorBuilder};
-use morse_traducer::utils::{get_reader, get_writer};
+use morse_traducer::utils::{get_bytes, get_reader, get_writer};
 use std::cell::RefCell;
 use std::io::BufRead;
 use std::rc::Rc;
 
 fn main() {
     let args = MorseArgs::parse();
-    let input_stream: Vec<String> = get_reader(&args.in_file)
-        .lines()
-        .map_while(Result::ok)
-        .collect();
+    let mut builder = TranslatorBuilder::new();
+    match (&args.morse_command, &args.traduction_type, &args.from) {
+        // raw PCM is decoded while it is read
+        (MorseCommand::Decode, MorseTraductionType::Audio, InputFormat::Pcm) => {
+            builder.input_pcm(Rc::new(RefCell::new(get_reader(&args.in_file))));
+        }
+        // decoding audio needs the raw WAV file
+        (MorseCommand::Decode, MorseTraductionType::Audio, InputFormat::Wav) => {
+            builder.input_audio(get_bytes(&args.in_file).unwrap());
+        }
+        _ => {
+            let input_stream: Vec<String> = get_reader(&args.in_file)
+                .lines()
+                .map_while(Result::ok)
+                .collect();
+            builder.input_stream(input_stream);
+        }
+    }
+    let separators = Separators {
+        lenient: args.lenient,
+        ..Separators::new(&args.letter_separator, &args.word_separator)
+    };
+    let normalizer = args.normalize.then(|| Normalizer {
+        spell_digits: args.spell_digits,
+        line_width: args.line_width,
+        ..Default::default()
+    });
+    let abbreviations = args.abbreviations.map(|_| {
+        let mut dictionary = Dictionary::standard();
+        if let Some(path) = &args.dictionary {
+            dictionary.extend(&String::from_utf8_lossy(&get_bytes(path).unwrap()));
+        }
+        dictionary
+    });
     let output_stream = Rc::new(RefCell::new(get_writer(&args.out_file).unwrap()));
 
-    let mut translator = TranslatorBuilder::new()
-        .input_stream(input_stream)
+    let mut translator = builder
         .output_stream(output_stream)
         .traduction_type(args.traduction_type)
+        .pan(args.pan)
+        .sample_format(args.sample_format)
+        .dither(args.dither)
+        .show_stats(args.stats)
+        .frequency(args.frequency)
+        .skim(args.skim)
+        .bandpass(args.bandpass)
+        .agc(args.agc)
+        .sample_rate(args.sample_rate)
+        .input_format(args.from)
+        .time_scale(args.time_scale)
+        .interval(args.interval)
+        .threshold(args.threshold)
+        .hysteresis(args.hysteresis)
+        .inverted(args.inverted)
+        .candidates(args.candidates)
+        .word_list(
+            args.word_list
+                .as_deref()
+                .map(|path| WordList::parse(&String::from_utf8_lossy(&get_bytes(path).unwrap()))),
+        )
+        .fuzzy(args.fuzzy)
+        .max_distance(args.max_distance)
+        .notation(args.notation)
+        .separators(separators)
+        .transliterate(args.transliterate)
+        .normalizer(normalizer)
+        .abbreviations(abbreviations)
+        .cut_numbers(args.cut_numbers)
+        .error_handling(args.errors)
+        .line_break(args.line_break)
+        .paragraph_pause(args.paragraph_pause)
+        .markup(args.markup)
+        .fit(args.fit)
+        .fit_mode(args.fit_mode)
+        .letter_wpm(args.letter_wpm)
+        .beacon(Beacon {
+            repeat: args.repeat,
+            duration: args.duration,
+            carrier: args.carrier,
+            gap: args.gap,
+            ..Default::default()
+        })
+        .abbreviation_style(args.abbreviations.unwrap_or(AbbreviationStyle::Annotate))
+        .output_format(args.format)
         .build_streamed()
         .unwrap();
     translator.translate(args.morse_command).unwrap();
@@ -0,0 +1,340 @@
+use crate::keying::{keying, line_gap, Key, LineBreak, LETTER_GAP_UNITS, WORD_GAP_UNITS};
+use crate::polyphonia::{notable_notes, Amplitude, Note, SAMPLE_RATE};
+use crate::{Letter, DOT_DURATION};
+
+/// setting changed partway through a message by a tag like `<wpm 25>`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tag {
+    Wpm(f32),
+    Frequency(f32),
+    /// seconds of silence
+    Pause(f32),
+    Volume(f32),
+    /// overall speed in words per minute, the letters keep the `wpm` speed
+    Farnsworth(f32),
+}
+
+/// piece of a line of marked up text
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Text(String),
+    Tag(Tag),
+}
+
+// longest pause of a tag, its silence is held in memory
+const MAX_PAUSE: f32 = 60.0;
+
+// `2s`, `500ms` or plain seconds
+fn parse_seconds(value: &str) -> Result<f32, String> {
+    let (number, scale) = match value.strip_suffix("ms") {
+        Some(number) => (number, 0.001),
+        None => (value.strip_suffix('s').unwrap_or(value), 1.0),
+    };
+    let seconds = number
+        .parse::<f32>()
+        .map(|seconds| seconds * scale)
+        .map_err(|_| format!("Not a duration: {}", value))?;
+    if !(seconds > 0.0 && seconds <= MAX_PAUSE) {
+        return Err(format!(
+            "Not a duration between 0 and {} seconds: {}",
+            MAX_PAUSE, value
+        ));
+    }
+    Ok(seconds)
+}
+
+fn parse_tag(tag: &str) -> Result<Tag, String> {
+    let mut parts = tag.split_whitespace();
+    let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+    let value = match (parts.next(), parts.next()) {
+        (Some(value), None) => value,
+        _ => return Err(format!("Markup tag needs one value: <{}>", tag)),
+    };
+    if name == "pause" {
+        return parse_seconds(value).map(Tag::Pause);
+    }
+    let number = value
+        .parse::<f32>()
+        .ok()
+        .filter(|number| *number > 0.0 && number.is_finite())
+        .ok_or_else(|| format!("Not a positive number in <{}>", tag))?;
+    match name.as_str() {
+        "wpm" => Ok(Tag::Wpm(number)),
+        "freq" | "frequency" => Ok(Tag::Frequency(number)),
+        "vol" | "volume" => Ok(Tag::Volume(number)),
+        "farnsworth" => Ok(Tag::Farnsworth(number)),
+        _ => Err(format!("Markup tag not found: <{}>", tag)),
+    }
+}
+
+/// text and tags of a line, e.g. `cq <wpm 25>de k1abc <pause 2s> k`
+pub fn parse(line: &str) -> Result<Vec<Item>, String> {
+    let mut items = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('<') {
+        if start > 0 {
+            items.push(Item::Text(rest[..start].into()));
+        }
+        let end = rest[start..]
+            .find('>')
+            .ok_or_else(|| format!("Markup tag not closed: {}", &rest[start..]))?;
+        items.push(Item::Tag(parse_tag(&rest[start + 1..start + end])?));
+        rest = &rest[start + end + 1..];
+    }
+    if !rest.is_empty() {
+        items.push(Item::Text(rest.into()));
+    }
+    Ok(items)
+}
+
+/// the text of a line without its tags, the spaces they leave are collapsed
+pub fn strip(line: &str) -> Result<String, String> {
+    let texts: Vec<String> = parse(line)?
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Text(text) => Some(text),
+            Item::Tag(_) => None,
+        })
+        .collect();
+    Ok(texts
+        .concat()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" "))
+}
+
+// stands for a tag while the text around it is edited, from the private use area
+const TAG_MARK: char = '\u{E000}';
+
+/// lines with every tag replaced by a mark that text edits leave alone,
+/// and the tags in order to put them back with `unmask`
+pub fn mask(lines: &[String]) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut masked = Vec::new();
+    let mut tags = Vec::new();
+    for line in lines {
+        let mut output = String::with_capacity(line.len());
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..]
+                .find('>')
+                .ok_or_else(|| format!("Markup tag not closed: {}", &rest[start..]))?;
+            parse_tag(&rest[start + 1..start + end])?;
+            output.push_str(&rest[..start]);
+            output.push(TAG_MARK);
+            tags.push(rest[start..=start + end].to_string());
+            rest = &rest[start + end + 1..];
+        }
+        output.push_str(rest);
+        masked.push(output);
+    }
+    Ok((masked, tags))
+}
+
+/// the tags taken by `mask` back in place of their marks
+pub fn unmask(lines: &[String], tags: Vec<String>) -> Vec<String> {
+    let mut tags = tags.into_iter();
+    lines
+        .iter()
+        .map(|line| {
+            line.split(TAG_MARK)
+                .enumerate()
+                .fold(String::new(), |mut output, (i, text)| {
+                    if i > 0 {
+                        output.push_str(&tags.next().unwrap_or_default());
+                    }
+                    output.push_str(text);
+                    output
+                })
+        })
+        .collect()
+}
+
+/// how the morse sounds at some point of the message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Voice {
+    pub wpm: f32,
+    pub frequency: f32,
+    pub volume: f32,
+    pub farnsworth: Option<f32>,
+}
+
+impl Default for Voice {
+    fn default() -> Self {
+        Voice {
+            wpm: 1.2 / DOT_DURATION,
+            frequency: notable_notes::A4.get_frequency(),
+            volume: Amplitude::Medium.scaling(),
+            farnsworth: None,
+        }
+    }
+}
+
+impl Voice {
+    /// seconds of a dot, by the PARIS standard word of 50 units
+    pub fn dot(&self) -> f32 {
+        1.2 / self.wpm
+    }
+
+    /// seconds of a unit of the gaps between letters and words, longer than
+    /// a dot when the farnsworth speed is slower than the letters
+    pub fn gap_unit(&self) -> f32 {
+        match self.farnsworth {
+            // the ARRL formula: the 19 gap units of PARIS take the time left over
+            Some(overall) if overall < self.wpm => {
+                (60.0 * self.wpm - 37.2 * overall) / (overall * self.wpm) / 19.0
+            }
+            _ => self.dot(),
+        }
+    }
+
+    pub fn apply(&mut self, tag: Tag) {
+        match tag {
+            Tag::Wpm(wpm) => self.wpm = wpm,
+            Tag::Frequency(frequency) => self.frequency = frequency,
+            Tag::Volume(volume) => self.volume = volume,
+            Tag::Farnsworth(overall) => self.farnsworth = Some(overall),
+            Tag::Pause(_) => {}
+        }
+    }
+
+    /// float samples of `seconds` of silence
+    pub fn silence(seconds: f32) -> Vec<f32> {
+        vec![0.0; (seconds * SAMPLE_RATE as f32) as usize]
+    }
+
+    /// float samples of `keys` sent with this voice
+    pub fn render(&self, keys: &[Key]) -> Vec<f32> {
+        let note = Note(self.frequency);
+        let mut output: Vec<f32> = Vec::new();
+        for key in keys {
+            match *key {
+                Key::Down(units) => output.extend(
+                    note.float_wave(self.dot() * units as f32, &Amplitude::Custom(self.volume)),
+                ),
+                Key::Up(units) if units < LETTER_GAP_UNITS => {
+                    output.extend(Self::silence(self.dot() * units as f32))
+                }
+                Key::Up(units) => output.extend(Self::silence(self.gap_unit() * units as f32)),
+            }
+        }
+        output
+    }
+}
+
+/// float samples of marked up lines, the tags change the voice from where they are
+/// and leave it as the last ones set it, lines end with `line_break` and paragraphs
+/// with at least `paragraph_pause` seconds
+pub fn render(
+    lines: &[String],
+    voice: &mut Voice,
+    line_break: LineBreak,
+    paragraph_pause: Option<f32>,
+) -> Result<Vec<f32>, String> {
+    let mut output: Vec<f32> = Vec::new();
+    // gap owed before the next letter: none, a letter or word gap, or a line end
+    let mut pending: Option<Vec<Key>> = None;
+    let mut paragraph = false;
+    let letter_gap = Key::Up(LETTER_GAP_UNITS);
+    for line in lines {
+        if line.trim().is_empty() {
+            paragraph = !output.is_empty();
+            continue;
+        }
+        let items = parse(line)?;
+        if !output.is_empty() {
+            let paragraph_gap = paragraph_pause
+                .filter(|_| paragraph)
+                .map(|pause| (pause / voice.gap_unit()).round() as u32);
+            pending = Some(line_gap(line_break, paragraph_gap));
+        }
+        paragraph = false;
+        for item in items {
+            let text = match item {
+                Item::Tag(Tag::Pause(seconds)) => {
+                    output.extend(Voice::silence(seconds));
+                    pending = None;
+                    continue;
+                }
+                Item::Tag(tag) => {
+                    voice.apply(tag);
+                    continue;
+                }
+                Item::Text(text) => text,
+            };
+            let letters = text
+                .trim()
+                .chars()
+                .map(Letter::from_char)
+                .collect::<Result<Vec<Letter>, String>>()?;
+            // a space on either side of a tag splits words
+            if text.starts_with(char::is_whitespace) && pending == Some(vec![letter_gap]) {
+                pending = Some(vec![Key::Up(WORD_GAP_UNITS)]);
+            }
+            if letters.is_empty() {
+                continue;
+            }
+            if let Some(gap) = pending.take() {
+                output.extend(voice.render(&gap));
+            }
+            output.extend(voice.render(&keying(letters.into_iter())));
+            pending = Some(vec![if text.ends_with(char::is_whitespace) {
+                Key::Up(WORD_GAP_UNITS)
+            } else {
+                letter_gap
+            }]);
+        }
+    }
+    Ok(output)
+}
+
+#[test]
+fn test_markup() {
+    assert_eq!(
+        parse("cq <wpm 25>de <pause 500ms>k").unwrap(),
+        vec![
+            Item::Text("cq ".into()),
+            Item::Tag(Tag::Wpm(25.0)),
+            Item::Text("de ".into()),
+            Item::Tag(Tag::Pause(0.5)),
+            Item::Text("k".into()),
+        ]
+    );
+    assert_eq!(strip("<freq 700>hi <vol 0.3>there").unwrap(), "hi there");
+    assert_eq!(strip("<wpm 25> cq <pause 2s> 599").unwrap(), "cq 599");
+    // text edits between mask and unmask don't reach the tags
+    let (lines, tags) = mask(&["<wpm 25>cq 5 <pause 2s>".to_string()]).unwrap();
+    let lines: Vec<String> = lines.iter().map(|line| line.replace('5', "five")).collect();
+    assert_eq!(unmask(&lines, tags), vec!["<wpm 25>cq five <pause 2s>"]);
+    assert!(parse("<wpm>").is_err());
+    assert!(parse("<speed 20>").is_err());
+    assert!(parse("<wpm 20").is_err());
+    assert!(parse("<wpm inf>").is_err());
+    assert!(parse("<pause -1s>").is_err());
+    assert!(parse("<pause 1e9s>").is_err());
+    assert!(parse("<pause 60s>").is_ok());
+
+    // PARIS at 20 wpm lasts 3 seconds, at 5 wpm overall 12 seconds
+    let mut voice = Voice::default();
+    voice.apply(Tag::Wpm(20.0));
+    let paris = |voice: &Voice| {
+        let line = ["paris ".to_string()];
+        render(&line, &mut voice.clone(), LineBreak::Space, None)
+            .unwrap()
+            .len() as f32
+            + {
+                // the word gap at the end is not sent
+                7.0 * voice.gap_unit() * SAMPLE_RATE as f32
+            }
+    };
+    assert!((paris(&voice) / SAMPLE_RATE as f32 - 3.0).abs() < 0.01);
+    voice.apply(Tag::Farnsworth(5.0));
+    assert!((paris(&voice) / SAMPLE_RATE as f32 - 12.0).abs() < 0.01);
+
+    // settings change partway through and pauses are silent
+    let lines = ["e <pause 1s><wpm 24>e".to_string()];
+    let mut voice = Voice::default();
+    let audio = render(&lines, &mut voice, LineBreak::Space, None).unwrap();
+    let seconds = audio.len() as f32 / SAMPLE_RATE as f32;
+    assert!((seconds - (0.1 + 1.0 + 0.05)).abs() < 0.01, "{}", seconds);
+    assert_eq!(voice.wpm, 24.0);
+}
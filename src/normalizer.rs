@@ -0,0 +1,172 @@
+/// kind of edit made by the normalizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Whitespace,
+    Symbol,
+    Digit,
+    Wrap,
+}
+
+/// one edit made to the text, to report what will really be sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// line of the input, starting at 1
+    pub line: usize,
+    pub kind: ChangeKind,
+    pub original: String,
+    pub replacement: String,
+}
+
+const DIGIT_NAMES: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// cleans text up before it is encoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalizer {
+    /// trim the lines and turn runs of whitespace into a single space
+    pub collapse_whitespace: bool,
+    /// words sent instead of symbols that have no code
+    pub symbols: Vec<(char, String)>,
+    /// send digits as words, `5` as `five`
+    pub spell_digits: bool,
+    /// longest line in characters, longer ones are wrapped between words
+    pub line_width: Option<usize>,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Normalizer {
+            collapse_whitespace: true,
+            symbols: [('%', "percent"), ('#', "nr"), ('&', "and"), ('*', "x")]
+                .map(|(symbol, word)| (symbol, word.to_string()))
+                .to_vec(),
+            spell_digits: false,
+            line_width: None,
+        }
+    }
+}
+
+impl Normalizer {
+    // word for a character, none when it is kept
+    fn word(&self, ch: char) -> Option<(ChangeKind, &str)> {
+        if let Some((_, word)) = self.symbols.iter().find(|(symbol, _)| *symbol == ch) {
+            return Some((ChangeKind::Symbol, word));
+        }
+        match ch.to_digit(10) {
+            Some(digit) if self.spell_digits => {
+                Some((ChangeKind::Digit, DIGIT_NAMES[digit as usize]))
+            }
+            _ => None,
+        }
+    }
+
+    // symbols and digits of a line replaced by words, spaced from their neighbours
+    fn spell(&self, line: &str, number: usize, changes: &mut Vec<Change>) -> String {
+        let mut output = String::with_capacity(line.len());
+        let mut chars = line.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let Some((kind, word)) = self.word(ch) else {
+                output.push(ch);
+                continue;
+            };
+            if !output.is_empty() && !output.ends_with(char::is_whitespace) {
+                output.push(' ');
+            }
+            output.push_str(word);
+            if chars.peek().is_some_and(|next| !next.is_whitespace()) {
+                output.push(' ');
+            }
+            changes.push(Change {
+                line: number,
+                kind,
+                original: ch.to_string(),
+                replacement: word.to_string(),
+            });
+        }
+        output
+    }
+
+    // lines no longer than the width, unless a single word is
+    fn wrap(width: usize, line: &str) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for word in line.split(' ') {
+            if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut current));
+            } else if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        lines.push(current);
+        lines
+    }
+
+    /// the normalized lines and the changes made to get them
+    pub fn normalize(&self, lines: &[String]) -> (Vec<String>, Vec<Change>) {
+        let mut output: Vec<String> = Vec::new();
+        let mut changes: Vec<Change> = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let number = i + 1;
+            let mut line = self.spell(line, number, &mut changes);
+            if self.collapse_whitespace {
+                let collapsed = line.split_whitespace().collect::<Vec<&str>>().join(" ");
+                if collapsed != line {
+                    changes.push(Change {
+                        line: number,
+                        kind: ChangeKind::Whitespace,
+                        original: line.clone(),
+                        replacement: collapsed.clone(),
+                    });
+                    line = collapsed;
+                }
+            }
+            match self.line_width {
+                Some(width) if line.chars().count() > width => {
+                    let wrapped = Self::wrap(width, &line);
+                    changes.push(Change {
+                        line: number,
+                        kind: ChangeKind::Wrap,
+                        original: line,
+                        replacement: wrapped.join("\n"),
+                    });
+                    output.extend(wrapped);
+                }
+                _ => output.push(line),
+            }
+        }
+        (output, changes)
+    }
+}
+
+#[test]
+fn test_normalizer() {
+    let lines = vec!["  rig  #2 at 50%\t power ".to_string()];
+    let (output, changes) = Normalizer::default().normalize(&lines);
+    assert_eq!(output, vec!["rig nr 2 at 50 percent power"]);
+    let kinds: Vec<ChangeKind> = changes.iter().map(|change| change.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ChangeKind::Symbol,
+            ChangeKind::Symbol,
+            ChangeKind::Whitespace
+        ]
+    );
+    assert_eq!(changes[1].original, "%");
+
+    let normalizer = Normalizer {
+        spell_digits: true,
+        line_width: Some(12),
+        ..Default::default()
+    };
+    let (output, changes) = normalizer.normalize(&["ur rst 599 tnx".to_string()]);
+    assert_eq!(
+        output,
+        vec!["ur rst five", "nine nine", "tnx"],
+        "{:?}",
+        changes
+    );
+    assert_eq!(changes.last().unwrap().kind, ChangeKind::Wrap);
+}
@@ -0,0 +1,227 @@
+use std::str::FromStr;
+
+/// ways of writing morse as text, all converted from and to the standard one:
+/// `.` and `-`, letters separated by a space and ` / ` between words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    Standard,
+    /// `·` and `−`
+    MiddleDot,
+    /// `•` and `—`
+    Bullet,
+    /// `.` and `_`
+    Underscore,
+    /// `di-dah-dit`
+    Spoken,
+    /// one digit a time unit: `1` key down, `0` key up, e.g. `10111000` for `a`
+    Binary,
+}
+
+impl FromStr for Notation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" | "ascii" => Ok(Notation::Standard),
+            "middle-dot" | "unicode" => Ok(Notation::MiddleDot),
+            "bullet" => Ok(Notation::Bullet),
+            "underscore" => Ok(Notation::Underscore),
+            "spoken" => Ok(Notation::Spoken),
+            "binary" => Ok(Notation::Binary),
+            _ => Err(format!("Notation not found: {}", s)),
+        }
+    }
+}
+
+// dot and dash of the notations written with one symbol each
+fn symbols(notation: Notation) -> Option<(char, char)> {
+    match notation {
+        Notation::Standard => Some(('.', '-')),
+        Notation::MiddleDot => Some(('·', '−')),
+        Notation::Bullet => Some(('•', '—')),
+        Notation::Underscore => Some(('.', '_')),
+        Notation::Spoken | Notation::Binary => None,
+    }
+}
+
+impl Notation {
+    /// guess the notation of `text`, `None` when it is not morse at all
+    pub fn detect(text: &str) -> Option<Self> {
+        let lower = text.to_ascii_lowercase();
+        if lower.contains("dah") || lower.contains("dit") {
+            return Some(Notation::Spoken);
+        }
+        let mut symbols = text.chars().filter(|ch| !ch.is_whitespace() && *ch != '/');
+        let first = symbols.next()?;
+        let rest: Vec<char> = symbols.collect();
+        let all =
+            |pattern: &str| pattern.contains(first) && rest.iter().all(|ch| pattern.contains(*ch));
+        if all("01") {
+            Some(Notation::Binary)
+        } else if all(".-") {
+            Some(Notation::Standard)
+        } else if all(".-_") {
+            Some(Notation::Underscore)
+        } else if all("·−-") {
+            Some(Notation::MiddleDot)
+        } else if all("•—") {
+            Some(Notation::Bullet)
+        } else {
+            None
+        }
+    }
+
+    /// standard morse written in this notation
+    pub fn render(&self, morse: &str) -> String {
+        match self {
+            Notation::Spoken => render_spoken(morse),
+            Notation::Binary => render_binary(morse),
+            _ => {
+                let (dot, dash) = symbols(*self).unwrap_or(('.', '-'));
+                morse
+                    .chars()
+                    .map(|ch| match ch {
+                        '.' => dot,
+                        '-' => dash,
+                        ch => ch,
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// text in this notation converted to standard morse
+    pub fn parse(&self, text: &str) -> Result<String, String> {
+        match self {
+            Notation::Spoken => parse_spoken(text),
+            Notation::Binary => parse_binary(text),
+            _ => {
+                let (dot, dash) = symbols(*self).unwrap_or(('.', '-'));
+                text.chars()
+                    .map(|ch| match ch {
+                        ch if ch == dot => Ok('.'),
+                        ch if ch == dash => Ok('-'),
+                        // the unicode minus is often typed as a hyphen
+                        '-' if *self == Notation::MiddleDot => Ok('-'),
+                        ch if ch.is_whitespace() || ch == '/' => Ok(ch),
+                        ch => Err(format!("Not a symbol of the {:?} notation: {}", self, ch)),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// convert text in any notation to standard morse, detecting the notation
+pub fn to_standard(text: &str) -> Result<String, String> {
+    match Notation::detect(text) {
+        Some(notation) => notation.parse(text),
+        None if text.trim().is_empty() => Ok(String::new()),
+        None => Err(format!("Notation not recognized: {}", text)),
+    }
+}
+
+// `di` for every dot but the last one of the letter, which is `dit`
+fn render_spoken(morse: &str) -> String {
+    let letters: Vec<String> = morse
+        .split_whitespace()
+        .map(|code| {
+            if code == "/" {
+                return code.to_string();
+            }
+            let last = code.len() - 1;
+            let syllables: Vec<&str> = code
+                .chars()
+                .enumerate()
+                .map(|(i, ch)| match ch {
+                    '-' => "dah",
+                    _ if i == last => "dit",
+                    _ => "di",
+                })
+                .collect();
+            syllables.join("-")
+        })
+        .collect();
+    letters.join(" ")
+}
+
+fn parse_spoken(text: &str) -> Result<String, String> {
+    let codes: Result<Vec<String>, String> = text
+        .split_whitespace()
+        .map(|letter| {
+            if letter == "/" {
+                return Ok(letter.to_string());
+            }
+            letter
+                .split('-')
+                .map(|syllable| match syllable.to_ascii_lowercase().as_str() {
+                    "di" | "dit" => Ok('.'),
+                    "dah" | "da" => Ok('-'),
+                    _ => Err(format!("Not a spoken symbol: {}", syllable)),
+                })
+                .collect()
+        })
+        .collect();
+    Ok(codes?.join(" "))
+}
+
+// the standard 1:3:7 timing, one digit a unit
+fn render_binary(morse: &str) -> String {
+    let mut output = String::new();
+    let mut gap = "";
+    for code in morse.split_whitespace() {
+        if code == "/" {
+            gap = "0000000";
+            continue;
+        }
+        output.push_str(gap);
+        let symbols: Vec<&str> = code
+            .chars()
+            .map(|ch| if ch == '-' { "111" } else { "1" })
+            .collect();
+        output.push_str(&symbols.join("0"));
+        gap = "000";
+    }
+    output
+}
+
+// runs of ones are dots or dashes, runs of zeros the gaps, lengths may be sloppy
+fn parse_binary(text: &str) -> Result<String, String> {
+    let digits: Vec<char> = text.chars().filter(|ch| !ch.is_whitespace()).collect();
+    if let Some(ch) = digits.iter().find(|ch| **ch != '0' && **ch != '1') {
+        return Err(format!("Not a binary digit: {}", ch));
+    }
+    let mut output = String::new();
+    for run in digits.chunk_by(|a, b| a == b) {
+        match (run[0], run.len()) {
+            ('1', 1) => output.push('.'),
+            ('1', _) => output.push('-'),
+            ('0', 1) => {}
+            ('0', 2..=5) => output.push(' '),
+            _ => output.push_str(" / "),
+        }
+    }
+    Ok(output.trim_matches([' ', '/']).to_string())
+}
+
+#[test]
+fn test_notations() {
+    let morse = "... --- ... / .--. .- .-. .. ...";
+    for notation in [
+        Notation::Standard,
+        Notation::MiddleDot,
+        Notation::Bullet,
+        Notation::Underscore,
+        Notation::Spoken,
+        Notation::Binary,
+    ] {
+        let text = notation.render(morse);
+        assert_eq!(Notation::detect(&text), Some(notation), "{}", text);
+        assert_eq!(to_standard(&text).unwrap(), morse, "{}", text);
+    }
+    assert_eq!(Notation::Spoken.render(".-"), "di-dah");
+    assert_eq!(Notation::Spoken.render("..."), "di-di-dit");
+    assert_eq!(Notation::Binary.render(".- ."), "101110001");
+    // sloppy timing is still read
+    assert_eq!(parse_binary("110111100100000001").unwrap(), "-- . / .");
+    assert!(to_standard("hello").is_err());
+}
@@ -2,6 +2,14 @@ use std::str::{self, FromStr};
 
 use clap::{self, Parser};
 
+use crate::abbreviations::AbbreviationStyle;
+use crate::cut_numbers::CutNumbers;
+use crate::fit::FitMode;
+use crate::keying::LineBreak;
+use crate::notation::Notation;
+use crate::prosigns::ErrorHandling;
+use crate::wav::sample::SampleFormat;
+
 /// tuple struct with two string slices with static lifetime (aka: as long as the program runs)
 #[derive(Debug, Clone)]
 pub enum MorseTraductionType {
@@ -24,6 +32,8 @@ impl FromStr for MorseTraductionType {
 pub enum MorseCommand {
     Encode,
     Decode,
+    /// the encoded message repeated with a carrier, audio only
+    Beacon,
 }
 
 impl FromStr for MorseCommand {
@@ -32,22 +42,66 @@ impl FromStr for MorseCommand {
         match s.to_ascii_lowercase().as_str() {
             "e" | "encode" => Ok(MorseCommand::Encode),
             "d" | "decode" => Ok(MorseCommand::Decode),
+            "b" | "beacon" => Ok(MorseCommand::Beacon),
             _ => Err(format!("Morse command not found: {}", s)),
         }
     }
 }
 
+/// shape of the decoded output
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("Output format not found: {}", s)),
+        }
+    }
+}
+
+/// encoding of the input to decode
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputFormat {
+    Wav,
+    Pcm,
+    Csv,
+    Json,
+    Signal,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wav" => Ok(InputFormat::Wav),
+            "pcm" => Ok(InputFormat::Pcm),
+            "csv" => Ok(InputFormat::Csv),
+            "json" => Ok(InputFormat::Json),
+            "signal" => Ok(InputFormat::Signal),
+            _ => Err(format!("Input format not found: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
 pub struct MorseArgs {
     /// Morse command:
     /// -encode
     /// -decode
+    /// -beacon
     pub morse_command: MorseCommand,
 
     /// Type of traduction from human readable text to morse:
     /// -text
-    /// -audio
+    /// -audio (when decoding the input is a WAV file)
     pub traduction_type: MorseTraductionType,
 
     /// Name of the file to read, if the value is "-" read from stdin
@@ -57,4 +111,210 @@ pub struct MorseArgs {
     /// Name of the file to read, if the value is "-" write to stdout
     #[clap(short, long, default_value = "-")]
     pub out_file: String,
+
+    /// Write stereo audio placing the morse from -1.0 (left) to 1.0 (right)
+    #[clap(long, allow_negative_numbers = true)]
+    pub pan: Option<f32>,
+
+    /// Format of the audio samples: u8, i16, i24, i32, f32
+    #[clap(long, default_value = "i16")]
+    pub sample_format: SampleFormat,
+
+    /// Apply TPDF dithering when converting the audio samples
+    #[clap(long)]
+    pub dither: bool,
+
+    /// Print on stderr the tone frequency and the speed of the sender while decoding audio
+    #[clap(long)]
+    pub stats: bool,
+
+    /// Pitch in Hz of the tone to decode, detected from the audio when not set
+    #[clap(long)]
+    pub frequency: Option<f32>,
+
+    /// Decode every signal found in the audio, printing frequency, start time and text
+    #[clap(long)]
+    pub skim: bool,
+
+    /// Width in Hz of a band-pass filter centered on the tone, applied before decoding audio
+    #[clap(long)]
+    pub bandpass: Option<f32>,
+
+    /// Level fading audio with automatic gain control before decoding it
+    #[clap(long)]
+    pub agc: bool,
+
+    /// Output format when decoding audio:
+    /// -text
+    /// -json (per character timestamp, SNR, confidence and alternatives)
+    #[clap(long, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Encoding of the input to decode:
+    /// -wav
+    /// -pcm (raw signed 16 bit little endian mono samples, decoded while they are read)
+    /// -csv (key events, one `time,state` per line, state is down/up)
+    /// -json (key events, an array of {"time": .., "state": ..} objects)
+    /// -signal (sampled values, one `value` or `time,value` per line)
+    #[clap(long, default_value = "wav")]
+    pub from: InputFormat,
+
+    /// Samples per second of the raw PCM audio to decode
+    #[clap(long, default_value = "44100")]
+    pub sample_rate: u32,
+
+    /// Seconds in a unit of the key event timestamps, e.g. 0.001 for milliseconds
+    #[clap(long, default_value = "1.0")]
+    pub time_scale: f32,
+
+    /// Seconds between the samples of a signal, taken from its time column when not set
+    #[clap(long)]
+    pub interval: Option<f32>,
+
+    /// Signal level switching the key, halfway between the low and high levels when not set
+    #[clap(long, allow_negative_numbers = true)]
+    pub threshold: Option<f32>,
+
+    /// Dead band around the signal threshold, as a fraction of the signal range
+    #[clap(long, default_value = "0.1")]
+    pub hysteresis: f32,
+
+    /// Low signal values mean key down
+    #[clap(long)]
+    pub inverted: bool,
+
+    /// Read every line as dots and dashes without letter gaps, listing this many
+    /// of the most likely readings with their score
+    #[clap(long)]
+    pub candidates: Option<usize>,
+
+    /// File with one known word per line, used to rank the readings and to correct words
+    #[clap(long)]
+    pub word_list: Option<String>,
+
+    /// Replace invalid codes, and words missing from the word list, with the nearest
+    /// valid ones by edit distance, corrected words are shown in square brackets
+    #[clap(long)]
+    pub fuzzy: bool,
+
+    /// Most dots, dashes and letter gaps changed by a single correction
+    #[clap(long, default_value = "2")]
+    pub max_distance: usize,
+
+    /// Notation of the morse text, detected when decoding if not set:
+    /// -standard (.-)
+    /// -unicode (·−)
+    /// -bullet (•—)
+    /// -underscore (._)
+    /// -spoken (di-dah)
+    /// -binary (10111, one digit a time unit)
+    #[clap(long)]
+    pub notation: Option<Notation>,
+
+    /// Separator between the letters of the morse text,
+    /// whitespace only matches any run of whitespace when decoding
+    #[clap(long, default_value = " ")]
+    pub letter_separator: String,
+
+    /// Separator between the words of the morse text
+    #[clap(long, default_value = " / ")]
+    pub word_separator: String,
+
+    /// When decoding, also accept `/` and `|` without spaces, three or more spaces
+    /// and line breaks between words, mixed in the same text
+    #[clap(long)]
+    pub lenient: bool,
+
+    /// When encoding, spell the characters missing from the morse table with
+    /// the ones it has: accents are dropped, ß becomes ss, typographic quotes
+    /// and dashes plain ones, and cyrillic is written in latin letters
+    #[clap(long)]
+    pub transliterate: bool,
+
+    /// When encoding, clean the text up first: collapse whitespace and spell out
+    /// symbols without a code (% as percent, # as nr, & as and, * as x),
+    /// the changes are printed with --stats
+    #[clap(long)]
+    pub normalize: bool,
+
+    /// With --normalize, send digits as words
+    #[clap(long)]
+    pub spell_digits: bool,
+
+    /// With --normalize, wrap lines longer than this many characters
+    #[clap(long)]
+    pub line_width: Option<usize>,
+
+    /// When decoding, show the meanings of q-codes and cw abbreviations:
+    /// -annotate (qth (location))
+    /// -expand (location)
+    /// with --format json every line is an object with the text and its annotations
+    #[clap(long)]
+    pub abbreviations: Option<AbbreviationStyle>,
+
+    /// File of extra abbreviations for --abbreviations, one a line followed by its
+    /// meaning, replacing the built-in ones with the same name
+    #[clap(long)]
+    pub dictionary: Option<String>,
+
+    /// Cut numbers: when encoding send these digits as letters in numbers,
+    /// e.g. 09 for 5nn and t, when decoding read them back in reports and
+    /// serials, `all` for every usual cut (0 t, 1 a, 2 u, 3 v, 5 e, 7 b, 8 d, 9 n)
+    #[clap(long)]
+    pub cut_numbers: Option<CutNumbers>,
+
+    /// Error prosign (eight dots or more) in the decoded text:
+    /// -mark (kept as <hh>)
+    /// -delete (removed with the word it corrects)
+    #[clap(long, default_value = "mark")]
+    pub errors: ErrorHandling,
+
+    /// Sent between the lines when encoding audio:
+    /// -space (a word gap)
+    /// -bt (the <bt> prosign)
+    /// -aa (the <aa> prosign)
+    #[clap(long, default_value = "space")]
+    pub line_break: LineBreak,
+
+    /// Seconds of silence at least between paragraphs, separated by blank lines,
+    /// when encoding audio
+    #[clap(long)]
+    pub paragraph_pause: Option<f32>,
+
+    /// Read tags in the text to encode, changing the audio from where they are:
+    /// <wpm 25>, <freq 700>, <pause 2s>, <vol 0.3> and <farnsworth 10>,
+    /// they are dropped when encoding text
+    #[clap(long)]
+    pub markup: bool,
+
+    /// Copies of the message sent by the beacon command, one unless a duration is set
+    #[clap(long)]
+    pub repeat: Option<usize>,
+
+    /// Seconds of audio of the beacon command at most, the last copy is cut
+    #[clap(long)]
+    pub duration: Option<f32>,
+
+    /// Seconds of steady tone after every copy of the beacon message
+    #[clap(long, default_value = "5")]
+    pub carrier: f32,
+
+    /// Seconds of silence after the carrier of the beacon
+    #[clap(long, default_value = "10")]
+    pub gap: f32,
+
+    /// Seconds the encoded audio must last, the speed is solved to fit them
+    /// and printed on stderr
+    #[clap(long)]
+    pub fit: Option<f32>,
+
+    /// What changes to fit the duration:
+    /// -wpm (the speed of the whole message)
+    /// -farnsworth (only the spacing, the letters keep --letter-wpm)
+    #[clap(long, default_value = "wpm")]
+    pub fit_mode: FitMode,
+
+    /// Speed of the letters when fitting with farnsworth spacing
+    #[clap(long, default_value = "12")]
+    pub letter_wpm: f32,
 }
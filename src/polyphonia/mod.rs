@@ -2,6 +2,7 @@
 pub mod amplitude;
 pub mod notable_notes;
 pub mod note;
+pub mod stereo;
 
 // public uses
 pub use amplitude::Amplitude;
@@ -20,6 +21,7 @@ fn get_w(frequency: f32, time: f32, sample_rate: f32) -> f32 {
     2.0 * std::f32::consts::PI * frequency * time / sample_rate
 }
 
+#[allow(dead_code)]
 fn natural_oscillator<F>(
     frequency: f32,
     time_start: f32,
@@ -1,4 +1,5 @@
 use super::{get_w, oscillator, Amplitude, MAX_AMPLITUDE, SAMPLE_RATE};
+use crate::dsp::Stage;
 
 pub struct Note(pub f32);
 
@@ -30,6 +31,27 @@ impl Note {
             .collect::<Vec<i16>>()
     }
 
+    /// same as `audio_wave` but keeps the samples as floats in the range [-1.0, 1.0]
+    pub fn float_wave(&self, secs: f32, volume: &Amplitude) -> Vec<f32> {
+        let nsamples = secs * SAMPLE_RATE as f32;
+        (0..nsamples as u32)
+            .map(|t| match *volume {
+                Amplitude::Silent => 0_f32,
+                _ => oscillator(
+                    get_w(self.get_frequency(), t as f32, SAMPLE_RATE as f32),
+                    volume.scaling(),
+                ),
+            })
+            .collect()
+    }
+
+    /// `float_wave` passed through a dsp stage, e.g. a `Chain` of filters
+    pub fn modified_wave(&self, secs: f32, volume: &Amplitude, stage: &mut dyn Stage) -> Vec<f32> {
+        let mut wave = self.float_wave(secs, volume);
+        stage.process_all(&mut wave);
+        wave
+    }
+
     pub fn audio_wave(&self, secs: f32, volume: &Amplitude) -> Vec<i16> {
         let nsamples = secs * SAMPLE_RATE as f32;
         (0..nsamples as u32)
@@ -43,3 +65,30 @@ impl Note {
             .collect()
     }
 }
+
+#[test]
+fn test_modified_wave() {
+    use crate::dsp::{Agc, Biquad, Chain};
+
+    let sample_rate = SAMPLE_RATE as f32;
+    let peak = |wave: &[f32]| {
+        wave[wave.len() / 2..]
+            .iter()
+            .fold(0_f32, |a, b| a.max(b.abs()))
+    };
+    // a band-pass filter keeps its own tone and cuts a far one
+    let mut chain = Chain::new();
+    chain.push(Biquad::band_pass(sample_rate, 700.0, 5.0));
+    let kept = Note(700.0).modified_wave(0.5, &Amplitude::Custom(0.5), &mut chain);
+    assert!((peak(&kept) - 0.5).abs() < 0.05, "{}", peak(&kept));
+    let mut chain = Chain::new();
+    chain.push(Biquad::band_pass(sample_rate, 700.0, 5.0));
+    let cut = Note(2000.0).modified_wave(0.5, &Amplitude::Custom(0.5), &mut chain);
+    assert!(peak(&cut) < 0.1, "{}", peak(&cut));
+
+    // the gain control then brings the filtered tone to its target
+    chain.push(Agc::new(sample_rate, 0.8));
+    let wave = Note(700.0).modified_wave(1.0, &Amplitude::Custom(0.1), &mut chain);
+    assert!((peak(&wave) - 0.8).abs() < 0.1, "{}", peak(&wave));
+    assert_eq!(wave.len(), SAMPLE_RATE as usize);
+}
@@ -0,0 +1,66 @@
+use std::f32::consts::FRAC_PI_4;
+
+/// gains for the left and right channel using a constant power pan law,
+/// `position` goes from -1.0 (left only) to 1.0 (right only)
+pub fn pan_gains(position: f32) -> (f32, f32) {
+    let angle = (position.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+    (f32::cos(angle), f32::sin(angle))
+}
+
+/// interleave one buffer per channel into frames (L R L R ... for stereo),
+/// shorter channels are padded with silence
+pub fn interleave(channels: &[&[i16]]) -> Vec<i16> {
+    let frames = channels.iter().map(|ch| ch.len()).max().unwrap_or(0);
+    let mut output = Vec::with_capacity(frames * channels.len());
+    for i in 0..frames {
+        output.extend(channels.iter().map(|ch| ch.get(i).copied().unwrap_or(0)));
+    }
+    output
+}
+
+/// place a mono wave in the stereo field, returns interleaved stereo frames
+pub fn pan(wave: &[i16], position: f32) -> Vec<i16> {
+    pan_mix(&[(wave, position)])
+}
+
+/// `pan` for float samples, which keep their precision
+pub fn pan_float(wave: &[f32], position: f32) -> Vec<f32> {
+    let (gain_l, gain_r) = pan_gains(position);
+    wave.iter()
+        .flat_map(|sample| [sample * gain_l, sample * gain_r])
+        .collect()
+}
+
+/// mix many mono sources (ex: different stations), each one with its own
+/// position in the stereo field, returns interleaved stereo frames
+pub fn pan_mix(sources: &[(&[i16], f32)]) -> Vec<i16> {
+    let frames = sources
+        .iter()
+        .map(|(wave, _)| wave.len())
+        .max()
+        .unwrap_or(0);
+    let mut left = vec![0_f32; frames];
+    let mut right = vec![0_f32; frames];
+    for (wave, position) in sources {
+        let (gain_l, gain_r) = pan_gains(*position);
+        for (i, sample) in wave.iter().enumerate() {
+            left[i] += *sample as f32 * gain_l;
+            right[i] += *sample as f32 * gain_r;
+        }
+    }
+    let to_i16 = |x: &f32| x.clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    interleave(&[
+        &left.iter().map(to_i16).collect::<Vec<i16>>(),
+        &right.iter().map(to_i16).collect::<Vec<i16>>(),
+    ])
+}
+
+#[test]
+fn test_pan() {
+    assert_eq!(pan(&[100, 200], -1.0), vec![100, 0, 200, 0]);
+    assert_eq!(pan(&[100, 200], 1.0), vec![0, 100, 0, 200]);
+    assert_eq!(interleave(&[&[1, 2], &[3]]), vec![1, 3, 2, 0]);
+    assert_eq!(pan_float(&[0.5], 1.0)[1], 0.5);
+    let (gain_l, gain_r) = pan_gains(0.0);
+    assert!((gain_l - gain_r).abs() < f32::EPSILON);
+}
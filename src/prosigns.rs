@@ -0,0 +1,97 @@
+use crate::morse_alphabet;
+use crate::Letter;
+use std::str::FromStr;
+
+// dots of the error prosign, senders often key a few more
+const ERROR_DOTS: usize = 8;
+
+/// what to do with the error prosign in the decoded text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorHandling {
+    /// keep it as `<hh>`
+    Mark,
+    /// remove it and the word it corrects
+    Delete,
+}
+
+impl FromStr for ErrorHandling {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mark" => Ok(ErrorHandling::Mark),
+            "delete" => Ok(ErrorHandling::Delete),
+            _ => Err(format!("Error handling not found: {}", s)),
+        }
+    }
+}
+
+/// whether `code` is the error prosign, eight dots or more
+pub fn is_error(code: &str) -> bool {
+    code.len() >= ERROR_DOTS && code.chars().all(|ch| ch == '.')
+}
+
+/// decoded text with the error prosigns handled, deleting removes the word
+/// sent before the prosign or the part of a word sent without a gap
+pub fn handle_errors(text: &str, handling: ErrorHandling) -> String {
+    let Letter(mark, _) = morse_alphabet::ERROR;
+    if handling == ErrorHandling::Mark || !text.contains(mark) {
+        return text.to_string();
+    }
+    let mut words: Vec<&str> = Vec::new();
+    for word in text.split(' ') {
+        let mut pieces = word.split(mark);
+        let mut last = pieces.next().unwrap_or_default();
+        for piece in pieces {
+            if last.is_empty() {
+                words.pop();
+            }
+            last = piece;
+        }
+        if !last.is_empty() || !word.contains(mark) {
+            words.push(last);
+        }
+    }
+    words.join(" ")
+}
+
+#[test]
+fn test_error_prosign() {
+    assert_eq!(Letter::from_str("........"), Ok(morse_alphabet::ERROR));
+    assert_eq!(Letter::from_str(".........."), Ok(morse_alphabet::ERROR));
+    assert!(is_error("........") && !is_error("......") && !is_error("..-....."));
+
+    let text = "the quick brwn <hh> brown fox";
+    assert_eq!(handle_errors(text, ErrorHandling::Mark), text);
+    assert_eq!(
+        handle_errors(text, ErrorHandling::Delete),
+        "the quick brown fox"
+    );
+    // the prosign sent right after the mistaken letters
+    assert_eq!(
+        handle_errors("the qic<hh> quick fox", ErrorHandling::Delete),
+        "the quick fox"
+    );
+    assert_eq!(
+        handle_errors("cq <hh> <hh> de k1abc", ErrorHandling::Delete),
+        "de k1abc"
+    );
+
+    // the prosign is heard in audio too
+    use crate::decoder::AudioDecoder;
+    use crate::polyphonia::{MAX_AMPLITUDE, SAMPLE_RATE};
+    let letters = "sso e so"
+        .chars()
+        .map(|ch| Letter::from_char(ch).unwrap())
+        .map(|letter| match letter {
+            Letter("e", _) => morse_alphabet::ERROR,
+            letter => letter,
+        });
+    let samples: Vec<f32> = Letter::concat_audio(letters)
+        .iter()
+        .map(|x| *x as f32 / MAX_AMPLITUDE)
+        .collect();
+    let decoded = AudioDecoder::new().decode(&samples, SAMPLE_RATE).unwrap();
+    let text = String::from_utf8(Letter::concat_text(decoded)).unwrap();
+    assert_eq!(text, "sso <hh> so");
+    assert_eq!(handle_errors(&text, ErrorHandling::Delete), "so");
+}
@@ -0,0 +1,146 @@
+use crate::language::LanguageModel;
+use crate::{morse_alphabet, Letter};
+use std::str::FromStr;
+
+/// one way of reading an unsegmented stream of dots and dashes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub text: String,
+    /// codes of the letters separated by spaces, as `Letter::concat_morse` writes them
+    pub morse: String,
+    /// log probability given by the language model, higher is better
+    pub score: f32,
+}
+
+// reading of the stream up to some position
+#[derive(Clone)]
+struct Partial {
+    text: String,
+    codes: Vec<&'static str>,
+    score: f32,
+}
+
+/// splits dots and dashes sent without letter gaps back into letters,
+/// a `/` in the stream is kept as a word space
+pub struct Segmenter<'m> {
+    model: &'m dyn LanguageModel,
+    /// readings kept at every position, more is slower but finds rarer texts
+    pub beam_width: usize,
+}
+
+impl<'m> Segmenter<'m> {
+    pub fn new(model: &'m dyn LanguageModel) -> Self {
+        Segmenter {
+            model,
+            beam_width: 256,
+        }
+    }
+
+    // dots, dashes and word spaces of the stream, whitespace is ignored
+    fn symbols(stream: &str) -> Result<Vec<char>, String> {
+        stream
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .map(|ch| match ch {
+                '.' | '-' | '/' => Ok(ch),
+                _ => Err(format!("Not a dot, a dash or a word space: {}", ch)),
+            })
+            .collect()
+    }
+
+    /// how many different letter sequences the stream can be read as
+    pub fn count(stream: &str) -> Result<u64, String> {
+        let symbols = Self::symbols(stream)?;
+        let mut ways = vec![0_u64; symbols.len() + 1];
+        ways[0] = 1;
+        for start in 0..symbols.len() {
+            if symbols[start] == '/' {
+                ways[start + 1] = ways[start + 1].saturating_add(ways[start]);
+                continue;
+            }
+            for end in start + 1..=symbols.len().min(start + morse_alphabet::LONGEST_CODE) {
+                let code: String = symbols[start..end].iter().collect();
+                if !code.contains('/') && Letter::from_str(&code).is_ok() {
+                    ways[end] = ways[end].saturating_add(ways[start]);
+                }
+            }
+        }
+        Ok(ways[symbols.len()])
+    }
+
+    /// the `top` most likely readings of the stream, best first
+    pub fn segment(&self, stream: &str, top: usize) -> Result<Vec<Candidate>, String> {
+        let symbols = Self::symbols(stream)?;
+        let mut beams: Vec<Vec<Partial>> = vec![Vec::new(); symbols.len() + 1];
+        beams[0].push(Partial {
+            text: String::new(),
+            codes: Vec::new(),
+            score: 0.0,
+        });
+        for start in 0..symbols.len() {
+            let mut beam = std::mem::take(&mut beams[start]);
+            beam.sort_by(|a, b| b.score.total_cmp(&a.score));
+            beam.truncate(self.beam_width.max(1));
+            for partial in beam {
+                for end in start + 1..=symbols.len().min(start + morse_alphabet::LONGEST_CODE) {
+                    let code: String = symbols[start..end].iter().collect();
+                    if code.contains('/') && code.len() > 1 {
+                        break;
+                    }
+                    let Ok(Letter(text, morse)) = Letter::from_str(&code) else {
+                        continue;
+                    };
+                    let mut next = partial.clone();
+                    for ch in text.chars() {
+                        next.score += self.model.next_score(&next.text, ch);
+                        next.text.push(ch);
+                    }
+                    next.codes.push(morse);
+                    beams[end].push(next);
+                }
+            }
+        }
+        let mut candidates: Vec<Candidate> = beams
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|partial| Candidate {
+                score: partial.score + self.model.end_score(&partial.text),
+                morse: partial.codes.join(" "),
+                text: partial.text,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates.truncate(top);
+        Ok(candidates)
+    }
+}
+
+#[test]
+fn test_segment() {
+    use crate::language::{NgramModel, WordList};
+
+    let stream = "......-...-..---";
+    assert!(Segmenter::count(stream).unwrap() > 1000);
+
+    let words = WordList::new(["hello", "help", "world"]);
+    let candidates = Segmenter::new(&words).segment(stream, 3).unwrap();
+    assert_eq!(candidates.len(), 3);
+    assert_eq!(candidates[0].text, "hello");
+    assert_eq!(candidates[0].morse, ".... . .-.. .-.. ---");
+    assert!(candidates[0].score > candidates[1].score);
+
+    let ngrams = NgramModel::english();
+    let candidates = Segmenter::new(&ngrams)
+        .segment("-...../.--..-.-......", 5)
+        .unwrap();
+    assert!(
+        candidates.iter().any(|c| c.text == "the paris"),
+        "{:?}",
+        candidates
+    );
+
+    assert!(Segmenter::count("..x").is_err());
+    // the error prosign is the longest code
+    assert_eq!(morse_alphabet::LONGEST_CODE, 8);
+}
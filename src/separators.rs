@@ -0,0 +1,148 @@
+/// strings between the letters and between the words of morse text,
+/// the standard ones are a space and ` / `
+#[derive(Debug, Clone, PartialEq)]
+pub struct Separators {
+    /// a separator made only of whitespace matches any run of whitespace
+    pub letter: String,
+    pub word: String,
+    /// also accept `/` and `|` without spaces, three or more spaces
+    /// and line breaks between words, mixed in the same text
+    pub lenient: bool,
+}
+
+impl Default for Separators {
+    fn default() -> Self {
+        Separators {
+            letter: " ".into(),
+            word: " / ".into(),
+            lenient: false,
+        }
+    }
+}
+
+// split on `separator`, any whitespace when it is only whitespace
+fn split<'a>(text: &'a str, separator: &'a str) -> Vec<&'a str> {
+    if separator.trim().is_empty() {
+        text.split_whitespace().collect()
+    } else {
+        text.split(separator)
+            .map(str::trim)
+            .filter(|piece| !piece.is_empty())
+            .collect()
+    }
+}
+
+impl Separators {
+    pub fn new(letter: &str, word: &str) -> Self {
+        Separators {
+            letter: letter.into(),
+            word: word.into(),
+            lenient: false,
+        }
+    }
+
+    /// codes of every word in `text`
+    pub fn words(&self, text: &str) -> Vec<Vec<String>> {
+        if self.lenient {
+            return self.lenient_words(text);
+        }
+        let words = if self.word.trim().is_empty() && !self.word.is_empty() {
+            // whitespace between words, only a longer run than between letters
+            text.split(self.word.as_str()).collect()
+        } else {
+            split(text, &self.word)
+        };
+        words
+            .into_iter()
+            .map(|word| {
+                split(word, &self.letter)
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<Vec<String>>()
+            })
+            .filter(|codes| !codes.is_empty())
+            .collect()
+    }
+
+    fn lenient_words(&self, text: &str) -> Vec<Vec<String>> {
+        let mut text = text.to_string();
+        if !self.word.trim().is_empty() {
+            text = text.replace(self.word.trim(), "/");
+        }
+        if !self.letter.trim().is_empty() {
+            text = text.replace(self.letter.trim(), " ");
+        }
+        let mut words: Vec<Vec<String>> = Vec::new();
+        let mut word: Vec<String> = Vec::new();
+        let mut code = String::new();
+        let mut spaces = 0;
+        for ch in text.chars().chain([' ']) {
+            let word_break = matches!(ch, '/' | '|' | '\n' | '\r');
+            if ch.is_whitespace() && !word_break {
+                spaces += 1;
+            }
+            if word_break || ch.is_whitespace() {
+                if !code.is_empty() {
+                    word.push(std::mem::take(&mut code));
+                }
+                if (word_break || spaces >= 3) && !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            if !ch.is_whitespace() && !word_break {
+                spaces = 0;
+                code.push(ch);
+            }
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+        words
+    }
+
+    /// `text` rewritten with the standard separators
+    pub fn to_standard(&self, text: &str) -> String {
+        let words: Vec<String> = self
+            .words(text)
+            .iter()
+            .map(|codes| codes.join(" "))
+            .collect();
+        words.join(" / ")
+    }
+
+    /// standard morse rewritten with these separators
+    pub fn render(&self, morse: &str) -> String {
+        let words: Vec<String> = Self::default()
+            .words(morse)
+            .iter()
+            .map(|codes| codes.join(&self.letter))
+            .collect();
+        words.join(&self.word)
+    }
+}
+
+#[test]
+fn test_separators() {
+    let standard = ".... .. / - .... . .-. .";
+    let strict = Separators::new("|", "||");
+    assert_eq!(strict.render(standard), "....|..||-|....|.|.-.|.");
+    assert_eq!(strict.to_standard("....|..||-|....|.|.-.|."), standard);
+    assert_eq!(
+        Separators::new(" ", "   ").to_standard(".... ..   - .... . .-. ."),
+        standard
+    );
+
+    let lenient = Separators {
+        lenient: true,
+        ..Default::default()
+    };
+    for text in [
+        ".... ../- .... . .-. .",
+        ".... .. | - .... . .-. .",
+        ".... ..   - .... .  .-. .",
+        ".... ..\n- .... . .-. .\n",
+        " .... .. / |- .... . .-. . /",
+    ] {
+        assert_eq!(lenient.to_standard(text), standard, "{:?}", text);
+    }
+}
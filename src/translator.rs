@@ -1,12 +1,40 @@
-use crate::parser::{MorseCommand, MorseTraductionType};
-use crate::polyphonia::SAMPLE_RATE;
+use crate::abbreviations::{AbbreviationStyle, Annotation, Dictionary};
+use crate::beacon::Beacon;
+use crate::correction::FuzzyDecoder;
+use crate::cut_numbers::CutNumbers;
+use crate::decoder::events::{self, KeyEvent};
+use crate::decoder::signal::parse_series;
+use crate::decoder::{AudioDecoder, SignalDecoder, Skimmer, StreamDecoder};
+use crate::fit::{FitMode, Length};
+use crate::json;
+use crate::keying::{self, keying_lines, keying_paragraphs, line_gap, Key, LineBreak};
+use crate::language::{LanguageModel, NgramModel, WordList};
+use crate::markup::{self, Voice};
+use crate::normalizer::Normalizer;
+use crate::notation::{self, Notation};
+use crate::parser::{InputFormat, MorseCommand, MorseTraductionType, OutputFormat};
+use crate::polyphonia::{notable_notes, stereo, Amplitude, SAMPLE_RATE};
+use crate::prosigns::{self, ErrorHandling};
+use crate::segmenter::Segmenter;
+use crate::separators::Separators;
+use crate::transliteration::transliterate;
+use crate::wav::sample::{Dither, SampleFormat};
+use crate::wav::wav_reader::WavReader;
 use crate::wav::wav_writer::{WavBuilder, WavOutBuffer};
-use crate::Letter;
+use crate::{Letter, DOT_DURATION};
 use std::cell::RefCell;
 use std::error::Error;
+use std::io::{self, Read};
 use std::rc::Rc;
 use std::str::{self, FromStr};
 
+// bytes read from the PCM input at a time, ~46 ms of audio at 44.1 kHz
+const PCM_BLOCK_SIZE: usize = 4096;
+// decoded words held back while streaming, for an error prosign to delete them
+const HELD_WORDS: usize = 2;
+// words written before the ones being decoded, giving context to cut numbers and abbreviations
+const CONTEXT_WORDS: usize = 2;
+
 pub trait MorseTranslator<T, W, R> {
     fn translate(&mut self, command: MorseCommand) -> Result<R, Box<dyn Error>>;
 
@@ -29,14 +57,148 @@ pub struct StreamedMorseTranslator<T: WavOutBuffer> {
     // this patter will create and use a StreamedMorseTranslator
     // or an AudioMorseTranslation trasparently
     input_stream: Vec<String>,
+    // raw content of the WAV file to decode
+    input_audio: Vec<u8>,
+    // s16le mono samples to decode while they are read
+    input_pcm: Option<Rc<RefCell<dyn Read>>>,
     pub output_stream: Rc<RefCell<T>>,
     pub traduction_type: MorseTraductionType,
+    // stereo position of the audio output, mono when not set
+    pub pan: Option<f32>,
+    pub sample_format: SampleFormat,
+    // dither when converting to a sample format smaller than the synthesized one
+    pub dither: bool,
+    // print the tone and the speed estimated while decoding audio on stderr
+    pub show_stats: bool,
+    // pitch of the tone to decode, searched in the audio when not set
+    pub frequency: Option<f32>,
+    // decode every signal found in the audio, one line each
+    pub skim: bool,
+    // layout of the decoded audio: plain text or a JSON report
+    pub output_format: OutputFormat,
+    // width in Hz of the band-pass filter around the tone before decoding audio
+    pub bandpass: Option<f32>,
+    // level the decoded audio with automatic gain control
+    pub agc: bool,
+    // samples per second of the raw PCM audio to decode
+    pub sample_rate: u32,
+    // encoding of the input to decode
+    pub input_format: InputFormat,
+    // seconds in a unit of the key event timestamps
+    pub time_scale: f32,
+    // seconds between the samples of a signal, read from its time column when not set
+    pub interval: Option<f32>,
+    // level switching the key in a signal, found from the signal when not set
+    pub threshold: Option<f32>,
+    // dead band around the threshold, as a fraction of the signal range
+    pub hysteresis: f32,
+    // low signal values mean key down
+    pub inverted: bool,
+    // split unsegmented dots and dashes, listing this many readings per line
+    pub candidates: Option<usize>,
+    // known words ranking the readings, a character model is used when not set
+    pub word_list: Option<WordList>,
+    // replace invalid codes and unknown words with the nearest ones, in square brackets
+    pub fuzzy: bool,
+    // furthest correction tried by the fuzzy decode
+    pub max_distance: usize,
+    // notation of the morse text, detected on decode and standard on encode when not set
+    pub notation: Option<Notation>,
+    // separators of the morse text, standard ones when encoding by default
+    pub separators: Separators,
+    // spell the characters missing from the morse table with the ones it has before encoding
+    pub transliterate: bool,
+    // clean the text up before encoding
+    pub normalizer: Option<Normalizer>,
+    // show the meanings of q-codes and abbreviations in the decoded text
+    pub abbreviations: Option<Dictionary>,
+    // meanings after the abbreviations or in their place
+    pub abbreviation_style: AbbreviationStyle,
+    // shorten the numbers when encoding and read them back when decoding
+    pub cut_numbers: Option<CutNumbers>,
+    // keep the error prosign in the decoded text or remove the word it corrects
+    pub error_handling: ErrorHandling,
+    // sent between the lines of the audio
+    pub line_break: LineBreak,
+    // seconds of silence at least between paragraphs of the audio
+    pub paragraph_pause: Option<f32>,
+    // read tags like <wpm 25> in the text to encode
+    pub markup: bool,
+    // repetitions, carrier and silence of the beacon command
+    pub beacon: Beacon,
+    // seconds the encoded audio lasts, the speed is solved to fit them
+    pub fit: Option<f32>,
+    // speed of the whole message or farnsworth spacing changed to fit
+    pub fit_mode: FitMode,
+    // speed of the letters fitted with farnsworth spacing
+    pub letter_wpm: f32,
 }
 
-impl<'l, T: WavOutBuffer> MorseTranslator<&str, Vec<Letter<'l>>, ()>
+impl<'l, T: WavOutBuffer> MorseTranslator<&str, Result<Vec<Letter<'l>>, String>, ()>
     for StreamedMorseTranslator<T>
 {
     fn translate(&mut self, command: MorseCommand) -> Result<(), Box<dyn Error>> {
+        // key event logs are decoded to text whatever the traduction type
+        if let (MorseCommand::Decode, InputFormat::Csv | InputFormat::Json) =
+            (&command, &self.input_format)
+        {
+            return self.decode_events();
+        }
+        if let (MorseCommand::Decode, InputFormat::Signal) = (&command, &self.input_format) {
+            return self.decode_signal();
+        }
+        let encoding = !matches!(command, MorseCommand::Decode);
+        // the text stages below only see the text between the tags
+        let tags = if encoding && self.markup {
+            let (lines, tags) = markup::mask(&self.input_stream)?;
+            self.input_stream = lines;
+            Some(tags)
+        } else {
+            None
+        };
+        if encoding && self.transliterate {
+            self.input_stream = self
+                .input_stream
+                .iter()
+                .map(|line| transliterate(line))
+                .collect();
+        }
+        if let (true, Some(normalizer)) = (encoding, &self.normalizer) {
+            let (lines, changes) = normalizer.normalize(&self.input_stream);
+            if self.show_stats {
+                for change in changes {
+                    eprintln!(
+                        "line {} {:?}: {:?} -> {:?}",
+                        change.line, change.kind, change.original, change.replacement
+                    );
+                }
+            }
+            self.input_stream = lines;
+        }
+        if let (true, Some(cut_numbers)) = (encoding, &self.cut_numbers) {
+            self.input_stream = self
+                .input_stream
+                .iter()
+                .map(|line| cut_numbers.shorten(line))
+                .collect();
+        }
+        if let Some(tags) = tags {
+            self.input_stream = markup::unmask(&self.input_stream, tags);
+        }
+        // the tags only change the audio
+        if let (MorseCommand::Encode, MorseTraductionType::Text, true) =
+            (&command, &self.traduction_type, self.markup)
+        {
+            self.input_stream = self
+                .input_stream
+                .iter()
+                .map(|line| markup::strip(line))
+                .collect::<Result<Vec<String>, String>>()?;
+        }
+        if let (MorseCommand::Beacon, MorseTraductionType::Text) = (&command, &self.traduction_type)
+        {
+            return Err("A beacon is only sent as audio".into());
+        }
         match self.traduction_type {
             MorseTraductionType::Text => self.translate_to_text(command),
             MorseTraductionType::Audio => self.translate_to_audio(command),
@@ -45,39 +207,100 @@ impl<'l, T: WavOutBuffer> MorseTranslator<&str, Vec<Letter<'l>>, ()>
 
     fn translate_to_audio(&mut self, command: MorseCommand) -> Result<(), Box<dyn Error>> {
         let read_cmd = match command {
-            MorseCommand::Encode => Self::encode,
-            MorseCommand::Decode => Self::decode,
+            MorseCommand::Encode | MorseCommand::Beacon => Self::encode,
+            // audio is decoded back into text
+            MorseCommand::Decode => return self.decode_audio(),
         };
 
-        let translated_lines = self.input_stream.iter().flat_map(|line| read_cmd(line));
-        let mut output = self.output_stream.as_ref().borrow_mut();
-        let wav = WavBuilder::new()
-            .sample_rate(SAMPLE_RATE)
-            .set_output(&mut *output);
-        let mut wav = wav.init()?;
-        wav.write_half_words(&Letter::concat_audio(translated_lines))?;
-        // Letter::concat_audio(translated_lines),
-        wav.close()?;
-        Ok(())
+        // the voice the message ends with, the beacon carrier goes on with it
+        let mut voice = Voice::default();
+        let audio = if self.markup {
+            if self.fit.is_some() {
+                return Err("A duration can't be fitted to marked up text".into());
+            }
+            markup::render(
+                &self.input_stream,
+                &mut voice,
+                self.line_break,
+                self.paragraph_pause,
+            )?
+        } else {
+            let translated_lines = self.read_lines(read_cmd)?;
+            match self.fit {
+                Some(seconds) => {
+                    let (audio, fitted) = self.fitted_audio(translated_lines, seconds)?;
+                    voice = fitted;
+                    audio
+                }
+                None => {
+                    let paragraph_gap =
+                        (self.paragraph_pause.unwrap_or(0.0) / DOT_DURATION).round() as u32;
+                    let keys = keying_lines(translated_lines, self.line_break, paragraph_gap);
+                    keying::render(&keys, &notable_notes::A4, DOT_DURATION, &Amplitude::Medium)
+                }
+            }
+        };
+        match command {
+            MorseCommand::Beacon => {
+                let beacon = Beacon {
+                    voice,
+                    ..self.beacon
+                };
+                self.write_audio(beacon.chunks(&audio))
+            }
+            _ => self.write_audio([audio]),
+        }
     }
 
     fn translate_to_text(&mut self, command: MorseCommand) -> Result<(), Box<dyn Error>> {
+        if let MorseCommand::Decode = command {
+            // line breaks are word spaces too, so the lines are one message
+            if self.separators.lenient {
+                self.input_stream = vec![self.input_stream.join("\n")];
+            }
+            self.input_stream = self
+                .input_stream
+                .iter()
+                .map(|line| self.separators.to_standard(line))
+                .map(|line| match self.notation {
+                    Some(notation) => notation.parse(&line),
+                    None => notation::to_standard(&line),
+                })
+                .collect::<Result<Vec<String>, String>>()?;
+        }
+        if let (MorseCommand::Decode, Some(top)) = (&command, self.candidates) {
+            return self.decode_candidates(top);
+        }
+        if let (MorseCommand::Decode, true) = (&command, self.fuzzy) {
+            return self.decode_fuzzy();
+        }
         let read_cmd = match command {
-            MorseCommand::Encode => Self::encode,
+            MorseCommand::Encode | MorseCommand::Beacon => Self::encode,
             MorseCommand::Decode => Self::decode,
         };
 
         let translate_cmd = match command {
-            MorseCommand::Encode => Letter::concat_morse,
+            MorseCommand::Encode | MorseCommand::Beacon => Letter::concat_morse,
             MorseCommand::Decode => Letter::concat_text,
         };
 
-        let translated_lines = self.input_stream.iter().map(|line| read_cmd(line));
+        let translated_lines = self.read_lines(read_cmd)?;
 
         let mut output = self.output_stream.as_ref().borrow_mut();
         let last_index = translated_lines.len() - 1;
-        for (i, line) in translated_lines.map(translate_cmd).enumerate() {
-            output.write_all(&line)?;
+        let standard = self.notation.is_none() && self.separators == Separators::default();
+        for (i, line) in translated_lines.into_iter().map(translate_cmd).enumerate() {
+            match command {
+                MorseCommand::Encode if !standard => {
+                    let notation = self.notation.unwrap_or(Notation::Standard);
+                    let morse = notation.render(str::from_utf8(&line)?);
+                    output.write_all(self.separators.render(&morse).as_bytes())?
+                }
+                MorseCommand::Decode => {
+                    output.write_all(self.decoded_text(str::from_utf8(&line)?).as_bytes())?
+                }
+                _ => output.write_all(&line)?,
+            }
             if i != last_index {
                 output.write_all(b"\n")?;
             }
@@ -86,31 +309,443 @@ impl<'l, T: WavOutBuffer> MorseTranslator<&str, Vec<Letter<'l>>, ()>
         Ok(())
     }
 
-    fn encode(line: &str) -> Vec<Letter<'l>> {
-        line.bytes()
-            .map(
-                |byte| match Letter::from_str(str::from_utf8(&[byte]).unwrap()) {
-                    Ok(letter) => letter,
-                    Err(err) => panic!("Character not supported {:?}", err),
-                },
-            )
-            .collect::<Vec<Letter<'_>>>()
+    fn encode(line: &str) -> Result<Vec<Letter<'l>>, String> {
+        line.chars()
+            .enumerate()
+            .map(|(i, ch)| {
+                Letter::from_char(ch)
+                    .map_err(|_| format!("Character not supported {:?} at column {}", ch, i + 1))
+            })
+            .collect()
     }
 
-    fn decode(line: &str) -> Vec<Letter<'l>> {
+    fn decode(line: &str) -> Result<Vec<Letter<'l>>, String> {
         line.split_whitespace()
-            .map(|morse_letter| match Letter::from_str(morse_letter) {
-                Ok(letter) => letter,
-                Err(err) => panic!("Character not supported {:?}", err),
+            .map(|morse_letter| {
+                Letter::from_str(morse_letter)
+                    .map_err(|_| format!("Code not supported {:?}", morse_letter))
+            })
+            .collect()
+    }
+}
+
+impl<T: WavOutBuffer> StreamedMorseTranslator<T> {
+    // letters of every input line, an error names the line it comes from
+    fn read_lines<'a, 'l>(
+        &'a self,
+        read_cmd: impl Fn(&'a str) -> Result<Vec<Letter<'l>>, String>,
+    ) -> Result<Vec<Vec<Letter<'l>>>, String> {
+        self.input_stream
+            .iter()
+            .enumerate()
+            .map(|(i, line)| read_cmd(line).map_err(|err| format!("{} of line {}", err, i + 1)))
+            .collect()
+    }
+
+    // decoded text with the error prosigns handled, its cut numbers expanded
+    // and the meanings of its abbreviations, when they are set
+    fn decoded_text(&self, text: &str) -> String {
+        self.decoded_text_as(text, &self.output_format)
+    }
+
+    fn decoded_text_as(&self, text: &str, format: &OutputFormat) -> String {
+        let text = &prosigns::handle_errors(text, self.error_handling);
+        let text = match &self.cut_numbers {
+            Some(cut_numbers) => &cut_numbers.expand(text),
+            None => text,
+        };
+        let Some(dictionary) = &self.abbreviations else {
+            return text.to_string();
+        };
+        match format {
+            OutputFormat::Json => json::object([
+                ("text", json::string(text)),
+                (
+                    "annotations",
+                    json::array(dictionary.annotate(text).iter().map(Annotation::to_json)),
+                ),
+            ]),
+            OutputFormat::Text => dictionary.render(text, self.abbreviation_style),
+        }
+    }
+
+    // complete words of the streamed `held` text that the words after them
+    // can no longer change, as `decoded_text` shows them in plain text,
+    // `context` keeps the last words written and all is settled when `finished`
+    fn settled_words(
+        &self,
+        held: &mut String,
+        context: &mut Vec<String>,
+        finished: bool,
+    ) -> Vec<String> {
+        *held = prosigns::handle_errors(held, self.error_handling);
+        let mut words: Vec<String> = held.split(' ').map(String::from).collect();
+        // the last word may still be received
+        let incomplete = if finished { None } else { words.pop() };
+        words.retain(|word| !word.is_empty());
+        let settled = words
+            .len()
+            .saturating_sub(if finished { 0 } else { HELD_WORDS });
+        let mut output = Vec::new();
+        for word in words.drain(..settled) {
+            let before = self.decoded_text_as(&context.join(" "), &OutputFormat::Text);
+            context.push(word);
+            let after = self.decoded_text_as(&context.join(" "), &OutputFormat::Text);
+            // the words before don't change with the ones after them
+            let new = after.strip_prefix(&before).unwrap_or(&after).trim_start();
+            if !new.is_empty() {
+                output.push(new.to_string());
+            }
+            if context.len() > CONTEXT_WORDS {
+                context.remove(0);
+            }
+        }
+        words.extend(incomplete);
+        *held = words.join(" ");
+        output
+    }
+
+    // voice sending `keys` in `seconds`, its speed is reported before the audio is made
+    fn fitted_voice(&self, keys: &[Key], seconds: f32) -> Result<Voice, String> {
+        let length = Length::of(keys);
+        let voice = Voice {
+            wpm: self.letter_wpm,
+            ..Default::default()
+        };
+        let voice = length.fit(seconds, voice, self.fit_mode)?;
+        match voice.farnsworth {
+            Some(overall) => eprintln!(
+                "{:.2} PARIS words in {} seconds: {:.1} wpm letters, {:.1} wpm farnsworth",
+                length.paris_words(),
+                seconds,
+                voice.wpm,
+                overall
+            ),
+            None => eprintln!(
+                "{:.2} PARIS words in {} seconds: {:.1} wpm",
+                length.paris_words(),
+                seconds,
+                voice.wpm
+            ),
+        }
+        Ok(voice)
+    }
+
+    // audio of `lines` lasting `seconds` and the voice sending it, the paragraph pauses
+    // keep their seconds after the line break and the rest is fitted in the time left
+    fn fitted_audio(
+        &self,
+        lines: Vec<Vec<Letter>>,
+        seconds: f32,
+    ) -> Result<(Vec<f32>, Voice), String> {
+        let paragraphs = keying_paragraphs(lines, self.line_break);
+        let line_gap = line_gap(self.line_break, None);
+        let pause = Voice::silence(self.paragraph_pause.unwrap_or(0.0));
+        let pauses = pause.len() * paragraphs.len().saturating_sub(1);
+        let keys = paragraphs.join(&line_gap[..]);
+        let voice = self.fitted_voice(&keys, seconds - pauses as f32 / SAMPLE_RATE as f32)?;
+        let paragraph_gap = [voice.render(&line_gap), pause].concat();
+        let audio: Vec<Vec<f32>> = paragraphs.iter().map(|keys| voice.render(keys)).collect();
+        Ok((audio.join(&paragraph_gap[..]), voice))
+    }
+
+    // mono float audio written as a single WAV file a chunk at a time,
+    // panned and converted to the sample format on the way
+    fn write_audio(
+        &self,
+        chunks: impl IntoIterator<Item = Vec<f32>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        let mut wav = WavBuilder::new();
+        wav.sample_rate(SAMPLE_RATE)
+            .sample_format(self.sample_format);
+        if self.pan.is_some() {
+            wav.num_channels(2);
+        }
+        let mut wav = wav.set_output(&mut *output).init()?;
+        let mut dither = Dither::default();
+        for audio in chunks {
+            let audio = match self.pan {
+                Some(position) => stereo::pan_float(&audio, position),
+                None => audio,
+            };
+            wav.write_float(&audio, self.dither.then_some(&mut dither))?
+        }
+        wav.close()?;
+        Ok(())
+    }
+
+    fn decode_audio(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(input_pcm) = self.input_pcm.clone() {
+            return self.decode_pcm(&mut *input_pcm.borrow_mut());
+        }
+        let wav = WavReader::from_bytes(&self.input_audio)?;
+        if self.skim {
+            return self.skim_audio(&wav);
+        }
+        let decoder = AudioDecoder {
+            frequency: self.frequency,
+            bandwidth: self.bandpass,
+            agc: self.agc,
+            ..Default::default()
+        };
+        if self.output_format == OutputFormat::Json {
+            let report = decoder.report(&wav.mono_samples(), wav.sample_rate());
+            let mut output = self.output_stream.as_ref().borrow_mut();
+            output.write_all(report.to_json().as_bytes())?;
+            output.flush()?;
+            return Ok(());
+        }
+        let transcript = decoder.transcribe(&wav.mono_samples(), wav.sample_rate())?;
+        if self.show_stats {
+            if let Some(frequency) = transcript.frequency {
+                eprintln!("tone {:.1} Hz", frequency);
+            }
+            for estimate in &transcript.speed {
+                eprintln!("{:.2}s {:.1} wpm", estimate.time, estimate.wpm);
+            }
+        }
+        let text = String::from_utf8(Letter::concat_text(transcript.letters))?;
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        output.write_all(self.decoded_text(&text).as_bytes())?;
+        output.flush()?;
+        Ok(())
+    }
+
+    // every line is read as dots and dashes without letter gaps
+    fn decode_candidates(&mut self, top: usize) -> Result<(), Box<dyn Error>> {
+        let ngrams;
+        let model: &dyn LanguageModel = match &self.word_list {
+            Some(word_list) => word_list,
+            None => {
+                ngrams = NgramModel::english();
+                &ngrams
+            }
+        };
+        let segmenter = Segmenter::new(model);
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        for (i, line) in self.input_stream.iter().enumerate() {
+            if i != 0 {
+                output.write_all(b"\n")?;
+            }
+            for candidate in segmenter.segment(line, top)? {
+                writeln!(
+                    output,
+                    "{:.2}\t{}\t{}",
+                    candidate.score,
+                    self.decoded_text(&candidate.text),
+                    candidate.morse
+                )?;
+            }
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    fn decode_fuzzy(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut decoder = FuzzyDecoder::new(self.word_list.as_ref());
+        decoder.max_distance = self.max_distance;
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        for (i, line) in self.input_stream.iter().enumerate() {
+            if i != 0 {
+                output.write_all(b"\n")?;
+            }
+            let words = decoder.decode_line(line);
+            let flagged: Vec<String> = words.iter().map(|word| word.flagged()).collect();
+            output.write_all(self.decoded_text(&flagged.join(" ")).as_bytes())?;
+            if self.show_stats {
+                for correction in words.iter().flat_map(|word| &word.corrections) {
+                    eprintln!(
+                        "{} -> {} ({} edits) {}",
+                        correction.received,
+                        correction.text,
+                        correction.distance,
+                        correction.alternatives.join(" ")
+                    );
+                }
+            }
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    fn decode_events(&mut self) -> Result<(), Box<dyn Error>> {
+        let log = self.input_stream.join("\n");
+        let events = match self.input_format {
+            InputFormat::Json => events::parse_json(&log)?,
+            _ => events::parse_csv(&log)?,
+        };
+        let events: Vec<KeyEvent> = events
+            .into_iter()
+            .map(|event| KeyEvent {
+                time: event.time * self.time_scale,
+                ..event
             })
-            .collect::<Vec<Letter<'_>>>()
+            .collect();
+        let transcript = events::decode_events(&events)?;
+        if self.show_stats {
+            for estimate in &transcript.speed {
+                eprintln!("{:.2}s {:.1} wpm", estimate.time, estimate.wpm);
+            }
+        }
+        let text = String::from_utf8(Letter::concat_text(transcript.letters))?;
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        output.write_all(self.decoded_text(&text).as_bytes())?;
+        output.flush()?;
+        Ok(())
+    }
+
+    fn decode_signal(&mut self) -> Result<(), Box<dyn Error>> {
+        let (values, interval) = parse_series(&self.input_stream.join("\n"))?;
+        let interval = self
+            .interval
+            .or(interval)
+            .ok_or("Sample interval not set and no time column in the signal")?;
+        let decoder = SignalDecoder {
+            threshold: self.threshold,
+            hysteresis: self.hysteresis,
+            inverted: self.inverted,
+            ..SignalDecoder::new(interval)
+        };
+        let report = decoder.report(&values);
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        if self.output_format == OutputFormat::Json {
+            output.write_all(report.to_json().as_bytes())?;
+            output.flush()?;
+            return Ok(());
+        }
+        if self.show_stats {
+            for estimate in &report.speed {
+                eprintln!("{:.2}s {:.1} wpm", estimate.time, estimate.wpm);
+            }
+            for character in &report.characters {
+                eprintln!(
+                    "{:.3}s\t{}\t{}",
+                    character.time, character.morse, character.text
+                );
+            }
+        }
+        output.write_all(self.decoded_text(&report.text()).as_bytes())?;
+        output.flush()?;
+        Ok(())
+    }
+
+    // decode blocks of samples as they arrive, writing every letter right away
+    fn decode_pcm(&mut self, input: &mut dyn Read) -> Result<(), Box<dyn Error>> {
+        let settings = AudioDecoder {
+            frequency: self.frequency,
+            bandwidth: self.bandpass,
+            agc: self.agc,
+            ..Default::default()
+        };
+        let mut decoder = StreamDecoder::new(settings, self.sample_rate);
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        let mut buffer = [0_u8; PCM_BLOCK_SIZE];
+        // first byte of a sample split between two reads
+        let mut odd_byte: Option<u8> = None;
+        let mut samples: Vec<f32> = Vec::with_capacity(PCM_BLOCK_SIZE / 2 + 1);
+        // decoded text not written yet and the last words written
+        let mut held = String::new();
+        let mut context: Vec<String> = Vec::new();
+        let mut written = false;
+        loop {
+            let read = match input.read(&mut buffer) {
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let finished = read == 0;
+            if finished {
+                held.push_str(&decoder.finish());
+            } else {
+                samples.clear();
+                let mut bytes = &buffer[..read];
+                if let Some(first) = odd_byte.take() {
+                    samples.push(SampleFormat::I16.decode(&[first, bytes[0]]));
+                    bytes = &bytes[1..];
+                }
+                let mut pairs = bytes.chunks_exact(2);
+                samples.extend((&mut pairs).map(|pair| SampleFormat::I16.decode(pair)));
+                odd_byte = pairs.remainder().first().copied();
+                held.push_str(&decoder.push(&samples));
+            }
+            let words = self.settled_words(&mut held, &mut context, finished);
+            if !words.is_empty() {
+                if written {
+                    output.write_all(b" ")?;
+                }
+                output.write_all(words.join(" ").as_bytes())?;
+                output.flush()?;
+                written = true;
+            }
+            if finished {
+                break;
+            }
+        }
+        if self.show_stats {
+            if let Some(frequency) = decoder.frequency() {
+                eprintln!("tone {:.1} Hz", frequency);
+            }
+        }
+        Ok(())
+    }
+
+    fn skim_audio(&mut self, wav: &WavReader) -> Result<(), Box<dyn Error>> {
+        let results = Skimmer::new().skim(&wav.mono_samples(), wav.sample_rate());
+        let mut output = self.output_stream.as_ref().borrow_mut();
+        for result in results {
+            writeln!(
+                output,
+                "{:.1} Hz\t{:.2}s\t{}",
+                result.frequency, result.start_time, result.text
+            )?;
+        }
+        output.flush()?;
+        Ok(())
     }
 }
 
 pub struct TranslatorBuilder<T: WavOutBuffer> {
     traduction_type: MorseTraductionType,
     input_stream: Option<Vec<String>>,
+    input_audio: Option<Vec<u8>>,
+    input_pcm: Option<Rc<RefCell<dyn Read>>>,
     output_stream: Option<Rc<RefCell<T>>>,
+    pan: Option<f32>,
+    sample_format: SampleFormat,
+    dither: bool,
+    show_stats: bool,
+    frequency: Option<f32>,
+    skim: bool,
+    output_format: OutputFormat,
+    bandpass: Option<f32>,
+    agc: bool,
+    sample_rate: u32,
+    input_format: InputFormat,
+    time_scale: f32,
+    interval: Option<f32>,
+    threshold: Option<f32>,
+    hysteresis: f32,
+    inverted: bool,
+    candidates: Option<usize>,
+    word_list: Option<WordList>,
+    fuzzy: bool,
+    max_distance: usize,
+    notation: Option<Notation>,
+    separators: Separators,
+    transliterate: bool,
+    normalizer: Option<Normalizer>,
+    abbreviations: Option<Dictionary>,
+    abbreviation_style: AbbreviationStyle,
+    cut_numbers: Option<CutNumbers>,
+    error_handling: ErrorHandling,
+    line_break: LineBreak,
+    paragraph_pause: Option<f32>,
+    markup: bool,
+    beacon: Beacon,
+    fit: Option<f32>,
+    fit_mode: FitMode,
+    letter_wpm: f32,
 }
 
 impl<T: WavOutBuffer> TranslatorBuilder<T> {
@@ -123,6 +758,16 @@ impl<T: WavOutBuffer> TranslatorBuilder<T> {
         self
     }
 
+    pub fn input_audio(&mut self, input_audio: Vec<u8>) -> &mut Self {
+        self.input_audio = Some(input_audio);
+        self
+    }
+
+    pub fn input_pcm(&mut self, input_pcm: Rc<RefCell<dyn Read>>) -> &mut Self {
+        self.input_pcm = Some(input_pcm);
+        self
+    }
+
     pub fn output_stream(&mut self, out_stream: Rc<RefCell<T>>) -> &mut Self {
         self.output_stream = Some(out_stream);
         self
@@ -133,19 +778,231 @@ impl<T: WavOutBuffer> TranslatorBuilder<T> {
         self
     }
 
+    pub fn pan(&mut self, pan: Option<f32>) -> &mut Self {
+        self.pan = pan;
+        self
+    }
+
+    pub fn sample_format(&mut self, sample_format: SampleFormat) -> &mut Self {
+        self.sample_format = sample_format;
+        self
+    }
+
+    pub fn dither(&mut self, dither: bool) -> &mut Self {
+        self.dither = dither;
+        self
+    }
+
+    pub fn show_stats(&mut self, show_stats: bool) -> &mut Self {
+        self.show_stats = show_stats;
+        self
+    }
+
+    pub fn frequency(&mut self, frequency: Option<f32>) -> &mut Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn skim(&mut self, skim: bool) -> &mut Self {
+        self.skim = skim;
+        self
+    }
+
+    pub fn output_format(&mut self, output_format: OutputFormat) -> &mut Self {
+        self.output_format = output_format;
+        self
+    }
+
+    pub fn bandpass(&mut self, bandpass: Option<f32>) -> &mut Self {
+        self.bandpass = bandpass;
+        self
+    }
+
+    pub fn agc(&mut self, agc: bool) -> &mut Self {
+        self.agc = agc;
+        self
+    }
+
+    pub fn sample_rate(&mut self, sample_rate: u32) -> &mut Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    pub fn input_format(&mut self, input_format: InputFormat) -> &mut Self {
+        self.input_format = input_format;
+        self
+    }
+
+    pub fn time_scale(&mut self, time_scale: f32) -> &mut Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    pub fn interval(&mut self, interval: Option<f32>) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn threshold(&mut self, threshold: Option<f32>) -> &mut Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn hysteresis(&mut self, hysteresis: f32) -> &mut Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    pub fn inverted(&mut self, inverted: bool) -> &mut Self {
+        self.inverted = inverted;
+        self
+    }
+
+    pub fn candidates(&mut self, candidates: Option<usize>) -> &mut Self {
+        self.candidates = candidates;
+        self
+    }
+
+    pub fn word_list(&mut self, word_list: Option<WordList>) -> &mut Self {
+        self.word_list = word_list;
+        self
+    }
+
+    pub fn fuzzy(&mut self, fuzzy: bool) -> &mut Self {
+        self.fuzzy = fuzzy;
+        self
+    }
+
+    pub fn max_distance(&mut self, max_distance: usize) -> &mut Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn notation(&mut self, notation: Option<Notation>) -> &mut Self {
+        self.notation = notation;
+        self
+    }
+
+    pub fn separators(&mut self, separators: Separators) -> &mut Self {
+        self.separators = separators;
+        self
+    }
+
+    pub fn transliterate(&mut self, transliterate: bool) -> &mut Self {
+        self.transliterate = transliterate;
+        self
+    }
+
+    pub fn normalizer(&mut self, normalizer: Option<Normalizer>) -> &mut Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    pub fn abbreviations(&mut self, abbreviations: Option<Dictionary>) -> &mut Self {
+        self.abbreviations = abbreviations;
+        self
+    }
+
+    pub fn abbreviation_style(&mut self, abbreviation_style: AbbreviationStyle) -> &mut Self {
+        self.abbreviation_style = abbreviation_style;
+        self
+    }
+
+    pub fn cut_numbers(&mut self, cut_numbers: Option<CutNumbers>) -> &mut Self {
+        self.cut_numbers = cut_numbers;
+        self
+    }
+
+    pub fn error_handling(&mut self, error_handling: ErrorHandling) -> &mut Self {
+        self.error_handling = error_handling;
+        self
+    }
+
+    pub fn line_break(&mut self, line_break: LineBreak) -> &mut Self {
+        self.line_break = line_break;
+        self
+    }
+
+    pub fn paragraph_pause(&mut self, paragraph_pause: Option<f32>) -> &mut Self {
+        self.paragraph_pause = paragraph_pause;
+        self
+    }
+
+    pub fn markup(&mut self, markup: bool) -> &mut Self {
+        self.markup = markup;
+        self
+    }
+
+    pub fn beacon(&mut self, beacon: Beacon) -> &mut Self {
+        self.beacon = beacon;
+        self
+    }
+
+    pub fn fit(&mut self, fit: Option<f32>) -> &mut Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn fit_mode(&mut self, fit_mode: FitMode) -> &mut Self {
+        self.fit_mode = fit_mode;
+        self
+    }
+
+    pub fn letter_wpm(&mut self, letter_wpm: f32) -> &mut Self {
+        self.letter_wpm = letter_wpm;
+        self
+    }
+
     pub fn build_streamed(&self) -> Result<StreamedMorseTranslator<T>, String> {
         Ok(StreamedMorseTranslator {
-            input_stream: self
-                .input_stream
-                .as_ref()
-                .expect("input_stream not set")
-                .clone(),
+            input_stream: match (&self.input_stream, &self.input_audio, &self.input_pcm) {
+                (Some(input_stream), _, _) => input_stream.clone(),
+                (None, Some(_), _) | (None, _, Some(_)) => Vec::new(),
+                (None, None, None) => panic!("input_stream not set"),
+            },
+            input_audio: self.input_audio.clone().unwrap_or_default(),
+            input_pcm: self.input_pcm.clone(),
             output_stream: self
                 .output_stream
                 .as_ref()
                 .expect("output_stream not set")
                 .clone(),
             traduction_type: self.traduction_type.clone(),
+            pan: self.pan,
+            sample_format: self.sample_format,
+            dither: self.dither,
+            show_stats: self.show_stats,
+            frequency: self.frequency,
+            skim: self.skim,
+            output_format: self.output_format.clone(),
+            bandpass: self.bandpass,
+            agc: self.agc,
+            sample_rate: self.sample_rate,
+            input_format: self.input_format.clone(),
+            time_scale: self.time_scale,
+            interval: self.interval,
+            threshold: self.threshold,
+            hysteresis: self.hysteresis,
+            inverted: self.inverted,
+            candidates: self.candidates,
+            word_list: self.word_list.clone(),
+            fuzzy: self.fuzzy,
+            max_distance: self.max_distance,
+            notation: self.notation,
+            separators: self.separators.clone(),
+            transliterate: self.transliterate,
+            normalizer: self.normalizer.clone(),
+            abbreviations: self.abbreviations.clone(),
+            abbreviation_style: self.abbreviation_style,
+            cut_numbers: self.cut_numbers.clone(),
+            error_handling: self.error_handling,
+            line_break: self.line_break,
+            paragraph_pause: self.paragraph_pause,
+            markup: self.markup,
+            beacon: self.beacon,
+            fit: self.fit,
+            fit_mode: self.fit_mode,
+            letter_wpm: self.letter_wpm,
         })
     }
 }
@@ -154,8 +1011,133 @@ impl<T: WavOutBuffer> Default for TranslatorBuilder<T> {
     fn default() -> Self {
         TranslatorBuilder {
             input_stream: None,
+            input_audio: None,
+            input_pcm: None,
             output_stream: None,
             traduction_type: MorseTraductionType::Text,
+            pan: None,
+            sample_format: SampleFormat::I16,
+            dither: false,
+            show_stats: false,
+            frequency: None,
+            skim: false,
+            output_format: OutputFormat::Text,
+            bandpass: None,
+            agc: false,
+            sample_rate: SAMPLE_RATE,
+            input_format: InputFormat::Wav,
+            time_scale: 1.0,
+            interval: None,
+            threshold: None,
+            hysteresis: 0.1,
+            inverted: false,
+            candidates: None,
+            word_list: None,
+            fuzzy: false,
+            max_distance: 2,
+            notation: None,
+            separators: Separators::default(),
+            transliterate: false,
+            normalizer: None,
+            abbreviations: None,
+            abbreviation_style: AbbreviationStyle::Annotate,
+            cut_numbers: None,
+            error_handling: ErrorHandling::Mark,
+            line_break: LineBreak::Space,
+            paragraph_pause: None,
+            markup: false,
+            beacon: Beacon::default(),
+            fit: None,
+            fit_mode: FitMode::Wpm,
+            letter_wpm: Voice::default().wpm,
         }
     }
 }
+
+#[test]
+fn test_translator() {
+    use std::io::Cursor;
+
+    let translate = |command: MorseCommand, input: &str| {
+        let output: Rc<RefCell<Cursor<Vec<u8>>>> = Rc::new(RefCell::new(Default::default()));
+        let mut translator = TranslatorBuilder::new()
+            .input_stream(input.lines().map(String::from).collect())
+            .output_stream(output.clone())
+            .build_streamed()
+            .unwrap();
+        translator
+            .translate(command)
+            .map(|_| String::from_utf8(output.borrow().get_ref().clone()).unwrap())
+            .map_err(|err| err.to_string())
+    };
+    assert_eq!(
+        translate(MorseCommand::Encode, "sos").unwrap(),
+        "... --- ..."
+    );
+    // characters without a code are reported instead of aborting
+    assert_eq!(
+        translate(MorseCommand::Encode, "ok\ncafé"),
+        Err("Character not supported 'é' at column 4 of line 2".into())
+    );
+    assert!(translate(MorseCommand::Encode, "a\tb").is_err());
+
+    // the corrected word is deleted on every decode path, streamed audio too
+    let output: Rc<RefCell<Cursor<Vec<u8>>>> = Rc::new(RefCell::new(Default::default()));
+    let mut translator = TranslatorBuilder::new()
+        .output_stream(output.clone())
+        .input_stream(vec!["-.-. --.- / -.. . / ........ / -.. .".into()])
+        .fuzzy(true)
+        .error_handling(ErrorHandling::Delete)
+        .build_streamed()
+        .unwrap();
+    translator.translate(MorseCommand::Decode).unwrap();
+    assert_eq!(output.borrow().get_ref(), b"cq de");
+
+    let letters = "cq de ww1abc e k1abc k".split(' ').flat_map(|word| {
+        let letters: Vec<Letter> = match word {
+            "e" => vec![crate::morse_alphabet::ERROR],
+            _ => word
+                .chars()
+                .map(|ch| Letter::from_char(ch).unwrap())
+                .collect(),
+        };
+        letters.into_iter().chain([crate::morse_alphabet::SPACE])
+    });
+    let pcm: Vec<u8> = Letter::concat_audio(letters)
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect();
+    let output: Rc<RefCell<Cursor<Vec<u8>>>> = Rc::new(RefCell::new(Default::default()));
+    let mut translator = TranslatorBuilder::new()
+        .output_stream(output.clone())
+        .input_stream(Vec::new())
+        .traduction_type(MorseTraductionType::Audio)
+        .input_pcm(Rc::new(RefCell::new(Cursor::new(pcm))))
+        .error_handling(ErrorHandling::Delete)
+        .build_streamed()
+        .unwrap();
+    translator.translate(MorseCommand::Decode).unwrap();
+    assert_eq!(output.borrow().get_ref(), b"cq de k1abc k");
+
+    // the paragraph pause keeps its seconds when the message is fitted
+    let output: Rc<RefCell<Cursor<Vec<u8>>>> = Rc::new(RefCell::new(Default::default()));
+    let mut translator = TranslatorBuilder::new()
+        .output_stream(output.clone())
+        .input_stream(vec!["paris".into(), "".into(), "paris".into()])
+        .traduction_type(MorseTraductionType::Audio)
+        .fit(Some(10.0))
+        .paragraph_pause(Some(2.0))
+        .build_streamed()
+        .unwrap();
+    translator.translate(MorseCommand::Encode).unwrap();
+    let wav = WavReader::from_bytes(output.borrow().get_ref()).unwrap();
+    assert!((wav.duration() - 10.0).abs() < 0.01, "{}", wav.duration());
+    let longest_silence = wav
+        .samples()
+        .fold((0, 0), |(longest, run), x| match x {
+            0.0 => (longest.max(run + 1), run + 1),
+            _ => (longest, 0),
+        })
+        .0;
+    assert!(longest_silence as f32 / SAMPLE_RATE as f32 >= 2.0);
+}
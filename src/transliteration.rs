@@ -0,0 +1,113 @@
+use crate::Letter;
+
+// latin spelling of a character missing from the morse table, none when unknown
+fn replacement(ch: char) -> Option<&'static str> {
+    let replacement = match ch {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        // cyrillic, russian and ukrainian letters
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' | 'ґ' => "g",
+        'д' => "d",
+        'е' | 'ё' | 'э' => "e",
+        'є' => "ye",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'ї' => "yi",
+        'й' => "j",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ы' => "y",
+        'ю' => "yu",
+        'я' => "ya",
+        // typographic punctuation
+        '“' | '”' | '„' | '«' | '»' | '″' => "\"",
+        '‘' | '’' | '‚' | '′' | '`' => "'",
+        '‐' | '‑' | '‒' | '–' | '—' | '−' => "-",
+        '…' => "...",
+        // marks outside the ITU table
+        '!' => ".",
+        ';' => ",",
+        '×' => "x",
+        '[' | '{' => "(",
+        ']' | '}' => ")",
+        ch if ch.is_whitespace() => " ",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+/// `text` with the characters missing from the morse table spelled with the
+/// ones it has, e.g. `é` as `e`, `ß` as `ss` and cyrillic in latin letters,
+/// characters without a spelling are kept as they are
+pub fn transliterate(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if Letter::from_char(ch).is_ok() {
+            output.push(ch);
+            continue;
+        }
+        let lower: String = ch.to_lowercase().collect();
+        let mut lower_chars = lower.chars();
+        match (lower_chars.next().and_then(replacement), lower_chars.next()) {
+            (Some(replacement), None) => output.push_str(replacement),
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+#[test]
+fn test_transliteration() {
+    assert_eq!(transliterate("Café Straße"), "Cafe Strasse");
+    assert_eq!(transliterate("“Ça va?” – oui…"), "\"ca va?\" - oui...");
+    assert_eq!(transliterate("Привет, мир"), "privet, mir");
+    assert_eq!(transliterate("Łódź\tnr 5"), "lodz nr 5");
+    // punctuation is sent, never read as morse
+    assert_eq!(Letter::from_char('.'), Ok(crate::morse_alphabet::PERIOD));
+    assert_eq!(Letter::from_char('/'), Ok(crate::morse_alphabet::SLASH));
+    // nothing to replace them with
+    assert_eq!(transliterate("ok 🙂"), "ok 🙂");
+    assert!(transliterate("Ærøskøbing, Zürich")
+        .chars()
+        .all(|ch| Letter::from_char(ch).is_ok()));
+}
@@ -1,5 +1,5 @@
 use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 
 pub fn get_reader(arg: &str) -> Box<dyn BufRead> {
     match arg {
@@ -11,14 +11,21 @@ pub fn get_reader(arg: &str) -> Box<dyn BufRead> {
     }
 }
 
+pub fn get_bytes(arg: &str) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    get_reader(arg).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
 // hack from https://github.com/rust-lang/rust/issues/72802#issuecomment-1101996578
 pub fn get_writer(arg: &str) -> io::Result<File> {
     match arg {
         "-" | "" => {
             let lock = io::stdout().lock();
-            #[cfg(any(target_family = "unix", target_family = "wasi"))]
+            // wasi is a target os, not a family
+            #[cfg(any(target_family = "unix", target_os = "wasi"))]
             unsafe {
-                use std::os::unix::io::{AsRawFd, FromRawFd};
+                use std::os::fd::{AsRawFd, FromRawFd};
                 Ok(std::fs::File::from_raw_fd(lock.as_raw_fd()))
             }
 
@@ -1 +1,3 @@
+pub mod sample;
+pub mod wav_reader;
 pub mod wav_writer;
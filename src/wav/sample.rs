@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+pub const FORMAT_PCM: u16 = 1;
+pub const FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// encodings of the samples supported inside the data chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    U8,
+    I16,
+    I24,
+    I32,
+    F32,
+}
+
+impl SampleFormat {
+    /// match the `format tag` and `bits per sample` found in the fmt chunk
+    pub fn from_header(format_tag: u16, bits_per_sample: u16) -> Option<Self> {
+        match (format_tag, bits_per_sample) {
+            (FORMAT_PCM, 8) => Some(SampleFormat::U8),
+            (FORMAT_PCM, 16) => Some(SampleFormat::I16),
+            (FORMAT_PCM, 24) => Some(SampleFormat::I24),
+            (FORMAT_PCM, 32) => Some(SampleFormat::I32),
+            (FORMAT_IEEE_FLOAT, 32) => Some(SampleFormat::F32),
+            _ => None,
+        }
+    }
+
+    pub fn format_tag(&self) -> u16 {
+        match self {
+            SampleFormat::F32 => FORMAT_IEEE_FLOAT,
+            _ => FORMAT_PCM,
+        }
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        match self {
+            SampleFormat::U8 => 8,
+            SampleFormat::I16 => 16,
+            SampleFormat::I24 => 24,
+            SampleFormat::I32 | SampleFormat::F32 => 32,
+        }
+    }
+
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits_per_sample() as usize / 8
+    }
+
+    // biggest integer value of the format, used to scale floats
+    fn full_scale(&self) -> f64 {
+        match self {
+            SampleFormat::U8 => 127.0,
+            SampleFormat::I16 => i16::MAX as f64,
+            SampleFormat::I24 => I24::MAX as f64,
+            SampleFormat::I32 => i32::MAX as f64,
+            SampleFormat::F32 => 1.0,
+        }
+    }
+
+    /// convert a float sample in the range [-1.0, 1.0] and append its
+    /// little endian representation to `out`, integer formats are dithered
+    /// when a `Dither` is given
+    pub fn encode(&self, sample: f32, dither: Option<&mut Dither>, out: &mut Vec<u8>) {
+        if let SampleFormat::F32 = self {
+            out.extend_from_slice(&sample.to_le_bytes());
+            return;
+        }
+        let noise = dither.map_or(0.0, |d| d.next_offset());
+        let scale = self.full_scale();
+        let value = (sample.clamp(-1.0, 1.0) as f64 * scale + noise)
+            .round()
+            .clamp(-scale - 1.0, scale);
+        match self {
+            SampleFormat::U8 => ((value + 128.0) as u8).write_le(out),
+            SampleFormat::I16 => (value as i16).write_le(out),
+            SampleFormat::I24 => I24(value as i32).write_le(out),
+            SampleFormat::I32 => (value as i32).write_le(out),
+            SampleFormat::F32 => unreachable!(),
+        }
+    }
+
+    /// read one little endian sample of this format as a float in the range [-1.0, 1.0],
+    /// `bytes` must be `bytes_per_sample` long
+    pub fn decode(&self, bytes: &[u8]) -> f32 {
+        match self {
+            SampleFormat::U8 => (bytes[0] as f32 - 128.0) / 128.0,
+            SampleFormat::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            SampleFormat::I24 => {
+                // shift up to sign extend the 24 bits
+                let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                value as f32 / (1 << 23) as f32
+            }
+            SampleFormat::I32 => {
+                (i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+                    / (1_u64 << 31) as f64) as f32
+            }
+            SampleFormat::F32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+impl FromStr for SampleFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "u8" => Ok(SampleFormat::U8),
+            "i16" => Ok(SampleFormat::I16),
+            "i24" => Ok(SampleFormat::I24),
+            "i32" => Ok(SampleFormat::I32),
+            "f32" | "float" => Ok(SampleFormat::F32),
+            _ => Err(format!("Sample format not found: {}", s)),
+        }
+    }
+}
+
+/// 24 bit signed sample, stored in the lower 3 bytes of an i32
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct I24(pub i32);
+
+impl I24 {
+    pub const MAX: i32 = (1 << 23) - 1;
+    pub const MIN: i32 = -(1 << 23);
+}
+
+/// types that can be written as they are in the data chunk
+pub trait Sample: Copy {
+    const FORMAT: SampleFormat;
+
+    fn write_le(&self, out: &mut Vec<u8>);
+}
+
+impl Sample for u8 {
+    const FORMAT: SampleFormat = SampleFormat::U8;
+    fn write_le(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Sample for i16 {
+    const FORMAT: SampleFormat = SampleFormat::I16;
+    fn write_le(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Sample for I24 {
+    const FORMAT: SampleFormat = SampleFormat::I24;
+    fn write_le(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.0.clamp(I24::MIN, I24::MAX).to_le_bytes()[..3]);
+    }
+}
+
+impl Sample for i32 {
+    const FORMAT: SampleFormat = SampleFormat::I32;
+    fn write_le(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Sample for f32 {
+    const FORMAT: SampleFormat = SampleFormat::F32;
+    fn write_le(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+/// triangular probability density function dither, the noise spans
+/// +-1 LSB of the destination format
+pub struct Dither {
+    // xorshift32 state, must never be 0
+    state: u32,
+}
+
+impl Default for Dither {
+    fn default() -> Self {
+        Self::new(0x9E37_79B9)
+    }
+}
+
+impl Dither {
+    pub fn new(seed: u32) -> Self {
+        Dither {
+            state: if seed == 0 { 1 } else { seed },
+        }
+    }
+
+    fn next_uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f64 / u32::MAX as f64
+    }
+
+    /// sum of two uniform variables, in LSB
+    pub fn next_offset(&mut self) -> f64 {
+        self.next_uniform() - self.next_uniform()
+    }
+}
+
+#[test]
+fn test_encode() {
+    let mut out = Vec::new();
+    SampleFormat::U8.encode(0.0, None, &mut out);
+    SampleFormat::U8.encode(1.0, None, &mut out);
+    SampleFormat::U8.encode(-1.0, None, &mut out);
+    assert_eq!(out, vec![128, 255, 1]);
+
+    out.clear();
+    SampleFormat::I24.encode(1.0, None, &mut out);
+    SampleFormat::I24.encode(-1.0, None, &mut out);
+    assert_eq!(out, vec![0xFF, 0xFF, 0x7F, 0x01, 0x00, 0x80]);
+
+    // dither never moves a sample more than 1 LSB
+    let mut dither = Dither::default();
+    for _ in 0..1000 {
+        out.clear();
+        SampleFormat::I16.encode(0.0, Some(&mut dither), &mut out);
+        let value = i16::from_le_bytes([out[0], out[1]]);
+        assert!((-1..=1).contains(&value));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::wav::sample::SampleFormat;
+use std::io::{self, Read};
+
+// format tag that moves the real format in the first bytes of the sub format GUID
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+fn malformed(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// description of the samples found in the fmt chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavSpec {
+    pub sample_rate: u32,
+    pub num_channels: u16,
+    pub bits_per_sample: u16,
+    pub sample_format: SampleFormat,
+}
+
+impl WavSpec {
+    fn parse(chunk: &[u8]) -> io::Result<Self> {
+        if chunk.len() < 16 {
+            return Err(malformed(format!(
+                "fmt chunk is {} bytes long, at least 16 expected",
+                chunk.len()
+            )));
+        }
+        let mut format_tag = le_u16(chunk, 0);
+        let num_channels = le_u16(chunk, 2);
+        let sample_rate = le_u32(chunk, 4);
+        let bits_per_sample = le_u16(chunk, 14);
+        if format_tag == FORMAT_EXTENSIBLE {
+            // cbSize(2) valid bits(2) channel mask(4) then the GUID
+            if chunk.len() < 26 {
+                return Err(malformed("extensible fmt chunk is too short".into()));
+            }
+            format_tag = le_u16(chunk, 24);
+        }
+        if num_channels == 0 {
+            return Err(malformed("fmt chunk declares 0 channels".into()));
+        }
+        if sample_rate == 0 {
+            return Err(malformed("fmt chunk declares a sample rate of 0".into()));
+        }
+        let sample_format =
+            SampleFormat::from_header(format_tag, bits_per_sample).ok_or_else(|| {
+                malformed(format!(
+                    "unsupported sample format: format tag {} with {} bits per sample",
+                    format_tag, bits_per_sample
+                ))
+            })?;
+        Ok(WavSpec {
+            sample_rate,
+            num_channels,
+            bits_per_sample,
+            sample_format,
+        })
+    }
+}
+
+/// WAV file loaded in memory, chunks other than fmt and data are skipped
+pub struct WavReader {
+    spec: WavSpec,
+    data: Vec<u8>,
+}
+
+impl WavReader {
+    pub fn new<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(malformed("missing RIFF/WAVE header".into()));
+        }
+        let mut spec = None;
+        let mut data = None;
+        let mut position = 12;
+        while position + 8 <= bytes.len() {
+            let id = &bytes[position..position + 4];
+            let size = le_u32(bytes, position + 4) as usize;
+            let start = position + 8;
+            let end = start.checked_add(size).filter(|end| *end <= bytes.len());
+            match (id, end) {
+                (b"fmt ", Some(end)) => spec = Some(WavSpec::parse(&bytes[start..end])?),
+                // a truncated data chunk is still readable
+                (b"data", end) => data = Some(&bytes[start..end.unwrap_or(bytes.len())]),
+                (_, Some(_)) => {}
+                (_, None) => {
+                    return Err(malformed(format!(
+                        "chunk {:?} declares {} bytes, only {} left",
+                        String::from_utf8_lossy(id),
+                        size,
+                        bytes.len() - start
+                    )))
+                }
+            }
+            if data.is_some() && spec.is_some() {
+                break;
+            }
+            // chunks are word aligned
+            position = start + size + size % 2;
+        }
+        let spec = spec.ok_or_else(|| malformed("missing fmt chunk".into()))?;
+        let data = data.ok_or_else(|| malformed("missing data chunk".into()))?;
+        let frame_size = spec.sample_format.bytes_per_sample() * spec.num_channels as usize;
+        // drop a trailing incomplete frame
+        let data = data[..data.len() - data.len() % frame_size].to_vec();
+        Ok(WavReader { spec, data })
+    }
+
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    pub fn num_channels(&self) -> u16 {
+        self.spec.num_channels
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        self.spec.bits_per_sample
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.data.len()
+            / (self.spec.sample_format.bytes_per_sample() * self.num_channels() as usize)
+    }
+
+    /// length of the audio in seconds
+    pub fn duration(&self) -> f32 {
+        self.num_frames() as f32 / self.sample_rate() as f32
+    }
+
+    /// every sample, channels interleaved, converted to f32 in the range [-1.0, 1.0]
+    pub fn samples(&self) -> impl Iterator<Item = f32> + '_ {
+        let sample_format = self.spec.sample_format;
+        self.data
+            .chunks_exact(sample_format.bytes_per_sample())
+            .map(move |bytes| sample_format.decode(bytes))
+    }
+
+    /// average of the channels of every frame
+    pub fn mono_samples(&self) -> Vec<f32> {
+        let num_channels = self.num_channels() as usize;
+        self.samples()
+            .collect::<Vec<f32>>()
+            .chunks_exact(num_channels)
+            .map(|frame| frame.iter().sum::<f32>() / num_channels as f32)
+            .collect()
+    }
+}
+
+#[test]
+fn test_read_back() {
+    use crate::wav::wav_writer::WavBuilder;
+    use std::io::Cursor;
+
+    let samples = [0.0, 0.5, -0.5, 0.25];
+    for sample_format in [
+        SampleFormat::U8,
+        SampleFormat::I16,
+        SampleFormat::I24,
+        SampleFormat::I32,
+        SampleFormat::F32,
+    ] {
+        let mut out: Cursor<Vec<u8>> = Default::default();
+        let wav_file = WavBuilder::new()
+            .sample_rate(8000)
+            .num_channels(2)
+            .sample_format(sample_format)
+            .set_output(&mut out);
+        let mut wav_file = wav_file.init().unwrap();
+        wav_file.write_float(&samples, None).unwrap();
+        wav_file.close().unwrap();
+
+        let reader = WavReader::new(Cursor::new(out.into_inner())).unwrap();
+        assert_eq!(reader.sample_rate(), 8000);
+        assert_eq!(reader.num_channels(), 2);
+        assert_eq!(reader.bits_per_sample(), sample_format.bits_per_sample());
+        assert_eq!(reader.num_frames(), 2);
+        for (read, written) in reader.samples().zip(samples) {
+            assert!((read - written).abs() < 0.01, "{:?}", sample_format);
+        }
+    }
+
+    assert!(WavReader::from_bytes(b"RIFF\0\0\0\0WAVE").is_err());
+    assert!(WavReader::from_bytes(b"RIFX\0\0\0\0WAVEfmt ").is_err());
+    assert!(WavReader::from_bytes(b"RIFF\0\0\0\0WAVEfmt \xFF\0\0\0").is_err());
+}
@@ -1,14 +1,15 @@
+use crate::polyphonia::stereo::interleave;
 use crate::polyphonia::SAMPLE_RATE;
+use crate::wav::sample::{Dither, Sample, SampleFormat, FORMAT_PCM};
 use std::{
-    borrow::Borrow,
     io::{self, Seek, SeekFrom, Write},
     marker::PhantomData,
 };
 
-const HEADER_SIZE: usize = 44;
-const RIFF_SIZE: u64 = 4;
-// header - riff - 4bytes of file length
-const OFFSET_SIZE: u32 = HEADER_SIZE as u32 - RIFF_SIZE as u32 - 4_u32;
+// "WAVE" + fmt chunk header + data chunk header, without the extended fmt or the fact chunk
+const PCM_HEADER_SIZE: usize = 44;
+// size of the fmt chunk when the cbSize field is present
+const EXTENDED_CHUNK_SIZE: u32 = 18;
 
 pub trait WavOutBuffer: Write + Seek {}
 impl<T: Write + Seek> WavOutBuffer for T {}
@@ -48,29 +49,54 @@ impl Default for WavOptions {
 
 impl WavOptions {
     pub fn align(&mut self) {
-        self.bytes_per_sample = self.bits_per_sample / 8;
+        // block align: one sample for every channel
+        self.bytes_per_sample = self.num_channels * self.bits_per_sample / 8;
         self.bytes_per_second = self.sample_rate * self.bytes_per_sample as u32;
+        // non PCM formats need the cbSize field in the fmt chunk
+        if self.pcm != FORMAT_PCM && self.chunk_size < EXTENDED_CHUNK_SIZE {
+            self.chunk_size = EXTENDED_CHUNK_SIZE;
+        }
+    }
+
+    pub fn sample_format(&self) -> Option<SampleFormat> {
+        SampleFormat::from_header(self.pcm, self.bits_per_sample)
     }
-}
 
-impl From<&WavOptions> for [u8; HEADER_SIZE] {
-    fn from(val: &WavOptions) -> Self {
-        let mut out = [0_u8; HEADER_SIZE];
-        out[..4].copy_from_slice(b"RIFF");
-        // data length + header_size - riff - 4bytes
-        out[4..8].copy_from_slice(&(val.written_data + OFFSET_SIZE).to_le_bytes());
-        out[8..12].copy_from_slice(b"WAVE");
-        out[12..16].copy_from_slice(b"fmt ");
-        out[16..20].copy_from_slice(&val.chunk_size.to_le_bytes());
-        out[20..22].copy_from_slice(&val.pcm.to_le_bytes());
-        out[22..24].copy_from_slice(&val.num_channels.to_le_bytes());
-        out[24..28].copy_from_slice(&val.sample_rate.to_le_bytes());
-        out[28..32].copy_from_slice(&val.bytes_per_second.to_le_bytes());
-        out[32..34].copy_from_slice(&val.bytes_per_sample.to_le_bytes());
-        out[34..36].copy_from_slice(&val.bits_per_sample.to_le_bytes());
-        out[36..40].copy_from_slice(b"data");
-        // data length
-        out[40..44].copy_from_slice(&(val.written_data + 32).to_le_bytes());
+    /// every header chunk written before the samples, filled with the
+    /// amount of data written so far
+    pub fn header(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PCM_HEADER_SIZE + 32);
+        out.extend_from_slice(b"RIFF");
+        // filled at the end, data length + header size - riff - 4bytes
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(b"WAVE");
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&self.chunk_size.to_le_bytes());
+        out.extend_from_slice(&self.pcm.to_le_bytes());
+        out.extend_from_slice(&self.num_channels.to_le_bytes());
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&self.bytes_per_second.to_le_bytes());
+        out.extend_from_slice(&self.bytes_per_sample.to_le_bytes());
+        out.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+        if self.chunk_size >= EXTENDED_CHUNK_SIZE {
+            // cbSize, no extra format bytes; pad the rest of the chunk
+            out.extend_from_slice(&0_u16.to_le_bytes());
+            out.resize(
+                out.len() + (self.chunk_size - EXTENDED_CHUNK_SIZE) as usize,
+                0,
+            );
+        }
+        if self.pcm != FORMAT_PCM {
+            // number of sample frames, mandatory outside of PCM
+            let frames = self.written_data / (self.bytes_per_sample.max(1) as u32);
+            out.extend_from_slice(b"fact");
+            out.extend_from_slice(&4_u32.to_le_bytes());
+            out.extend_from_slice(&frames.to_le_bytes());
+        }
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&self.written_data.to_le_bytes());
+        let riff_size = (out.len() - 8) as u32 + self.written_data;
+        out[4..8].copy_from_slice(&riff_size.to_le_bytes());
         out
     }
 }
@@ -104,6 +130,12 @@ impl WavBuilder {
         self.wav_opts.bits_per_sample = bits_per_sample;
         self
     }
+    /// set both `pcm` and `bits_per_sample` for the given format
+    pub fn sample_format(&mut self, sample_format: SampleFormat) -> &mut Self {
+        self.wav_opts.pcm = sample_format.format_tag();
+        self.wav_opts.bits_per_sample = sample_format.bits_per_sample();
+        self
+    }
     pub fn set_output<'a, T: WavOutBuffer>(
         &mut self,
         out_buffer: &'a mut T,
@@ -162,8 +194,17 @@ where
     }
 
     pub fn init(mut self) -> io::Result<WavWriter<'a, T, Initiated>> {
+        if self.wav_opts.sample_format().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unsupported sample format: pcm {} with {} bits per sample",
+                    self.wav_opts.pcm, self.wav_opts.bits_per_sample
+                ),
+            ));
+        }
         self.header_position = self.out_buffer.stream_position()?;
-        let header: [u8; HEADER_SIZE] = self.wav_opts.borrow().into();
+        let header = self.wav_opts.header();
         // don't count in written_data
         self.out_buffer.write_all(&header)?;
         self.wav_opts.written_data = 0;
@@ -172,21 +213,106 @@ where
 }
 
 // behaviour after initiating the writer
-// functions available are `write_half_words` and `close`
+// functions available are `write_half_words`, `write_frames`, `write_channels`,
+// `write_samples`, `write_float` and `close`
 impl<'a, T: WavOutBuffer> WavWriter<'a, T, Initiated> {
-    pub fn write_half_words(&mut self, data: &[i16]) -> io::Result<()> {
-        for half_word in data {
-            self.write_all(&(*half_word as u16).to_le_bytes())?;
+    pub fn num_channels(&self) -> u16 {
+        self.wav_opts.num_channels
+    }
+
+    pub fn sample_format(&self) -> SampleFormat {
+        // checked in `init`
+        self.wav_opts.sample_format().unwrap()
+    }
+
+    fn check_frames(&self, len: usize) -> io::Result<()> {
+        let num_channels = self.num_channels() as usize;
+        if num_channels == 0 || !len.is_multiple_of(num_channels) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} samples can't be split in frames of {} channels",
+                    len, num_channels
+                ),
+            ));
         }
         Ok(())
     }
 
+    /// write interleaved samples already in the format of the file
+    pub fn write_samples<S: Sample>(&mut self, data: &[S]) -> io::Result<()> {
+        if S::FORMAT != self.sample_format() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "can't write {:?} samples in a {:?} file",
+                    S::FORMAT,
+                    self.sample_format()
+                ),
+            ));
+        }
+        self.check_frames(data.len())?;
+        let mut bytes = Vec::with_capacity(data.len() * S::FORMAT.bytes_per_sample());
+        for sample in data {
+            sample.write_le(&mut bytes);
+        }
+        self.write_all(&bytes)
+    }
+
+    /// write interleaved float samples in the range [-1.0, 1.0] converting
+    /// them to the format of the file, optionally with TPDF dithering
+    pub fn write_float(&mut self, data: &[f32], mut dither: Option<&mut Dither>) -> io::Result<()> {
+        self.check_frames(data.len())?;
+        let sample_format = self.sample_format();
+        let mut bytes = Vec::with_capacity(data.len() * sample_format.bytes_per_sample());
+        for sample in data {
+            sample_format.encode(*sample, dither.as_deref_mut(), &mut bytes);
+        }
+        self.write_all(&bytes)
+    }
+
+    /// write 16 bit samples, only in an i16 file
+    pub fn write_half_words(&mut self, data: &[i16]) -> io::Result<()> {
+        self.write_samples(data)
+    }
+
+    /// write interleaved samples, `data` must contain whole frames
+    /// (one sample for every channel: L R L R ... for stereo)
+    pub fn write_frames(&mut self, data: &[i16]) -> io::Result<()> {
+        self.write_samples(data)
+    }
+
+    /// interleave one buffer per channel and write them as frames,
+    /// all the buffers must have the same length
+    pub fn write_channels(&mut self, channels: &[&[i16]]) -> io::Result<()> {
+        if channels.len() != self.num_channels() as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "got {} channels, the writer was built with {}",
+                    channels.len(),
+                    self.num_channels()
+                ),
+            ));
+        }
+        if channels
+            .windows(2)
+            .any(|pair| pair[0].len() != pair[1].len())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "channels have different lengths",
+            ));
+        }
+        self.write_frames(&interleave(channels))
+    }
+
     pub fn close(self) -> io::Result<()> {
         self.out_buffer.flush()?;
         let last_pos = self.out_buffer.stream_position()?;
         let offset: i64 = (last_pos - self.header_position).try_into().unwrap();
         self.out_buffer.seek(SeekFrom::Current(-offset))?;
-        let header: [u8; HEADER_SIZE] = self.wav_opts.borrow().into();
+        let header = self.wav_opts.header();
         // don't count in written_data
         self.out_buffer.write_all(&header)?;
         self.out_buffer.flush()?;
@@ -248,3 +374,53 @@ fn test_file() {
         .unwrap();
     wav_file.close().unwrap()
 }
+
+#[test]
+fn test_frames() {
+    use std::io::Cursor;
+
+    let mut out: Cursor<Vec<u8>> = Default::default();
+    let wav_file = WavBuilder::new().num_channels(2).set_output(&mut out);
+    let mut wav_file = wav_file.init().unwrap();
+    assert!(wav_file.write_frames(&[1, 2, 3]).is_err());
+    assert!(wav_file.write_channels(&[&[1, 2], &[3]]).is_err());
+    wav_file.write_channels(&[&[1, 2], &[3, 4]]).unwrap();
+    wav_file.close().unwrap();
+
+    let out = out.into_inner();
+    // block align and byte rate account for both channels
+    assert_eq!(&out[32..34], &4_u16.to_le_bytes());
+    assert_eq!(&out[28..32], &(SAMPLE_RATE * 4).to_le_bytes());
+    assert_eq!(&out[PCM_HEADER_SIZE..], &[1, 0, 3, 0, 2, 0, 4, 0]);
+}
+
+#[test]
+fn test_float_header() {
+    use std::io::Cursor;
+
+    let mut out: Cursor<Vec<u8>> = Default::default();
+    let wav_file = WavBuilder::new()
+        .sample_format(SampleFormat::F32)
+        .num_channels(2)
+        .set_output(&mut out);
+    let mut wav_file = wav_file.init().unwrap();
+    assert!(wav_file.write_samples(&[1_i16, 2]).is_err());
+    assert!(wav_file.write_half_words(&[1, 2]).is_err());
+    wav_file.write_float(&[0.5, -0.5, 1.0, -1.0], None).unwrap();
+    wav_file.close().unwrap();
+
+    let out = out.into_inner();
+    // fmt chunk with cbSize, then fact with 2 frames
+    assert_eq!(&out[16..20], &18_u32.to_le_bytes());
+    assert_eq!(&out[20..22], &3_u16.to_le_bytes());
+    assert_eq!(&out[38..42], b"fact");
+    assert_eq!(&out[46..50], &2_u32.to_le_bytes());
+    assert_eq!(&out[50..54], b"data");
+    assert_eq!(&out[54..58], &16_u32.to_le_bytes());
+    assert_eq!(&out[4..8], &(out.len() as u32 - 8).to_le_bytes());
+    assert_eq!(&out[58..62], &0.5_f32.to_le_bytes());
+
+    let mut out: Cursor<Vec<u8>> = Default::default();
+    let wav_file = WavBuilder::new().bits_per_sample(12).set_output(&mut out);
+    assert!(wav_file.init().is_err());
+}